@@ -45,16 +45,13 @@ fn main() {
     }
 
     code.push_str("use crate::tool::tools::Tool;\n");
-    code.push_str(
-        "pub fn get_user_tools() -> Vec<Tool> {\n\
-            let mut v = Vec::new();\n\
-            ");
+    code.push_str("pub fn get_user_tools() -> Vec<Tool> {\n    vec![\n");
 
     for m in &modules {
-        code.push_str(&format!("v.push({m}_tool());\n", m = m));
+        code.push_str(&format!("        {m}_tool(),\n", m = m));
     }
 
-    code.push_str("\n    v\n}\n");
+    code.push_str("    ]\n}\n");
 
     fs::write(&dest_path, code).unwrap();
 }
\ No newline at end of file
@@ -16,7 +16,7 @@
 use serde_json::Value;
 use std::{env, io};
 
-use crate::api::{client_util::create_messages, ChatClient, ChatResponse};
+use crate::api::{client_util::create_messages, ChatClient, ChatResponse, TokenUsage};
 
 static LLM_PROTOCOL: &str = "https";
 static LLM_HOST: &str = "api.anthropic.com";
@@ -26,6 +26,7 @@ pub struct AnthropicClient {
     model: String,
     system_prompt: String,
     max_tokens: usize,
+    enabled_tools: Option<Vec<String>>,
 }
 
 impl ChatClient for AnthropicClient {
@@ -45,10 +46,11 @@ impl ChatClient for AnthropicClient {
 
         let send_body = Self::build_json_body(&self.model, self.max_tokens, messages);
 
-        let response = Self::send_request_and_handle_response(&send_body)?;
+        let (response, usage) = Self::send_request_and_handle_response(&send_body)?;
         Ok(ChatResponse {
             content: response,
             tool_calls: None,
+            usage,
         })
     }
 
@@ -71,6 +73,14 @@ impl ChatClient for AnthropicClient {
     fn system_prompt(&self) -> String {
         self.system_prompt.to_string()
     }
+
+    fn update_enabled_tools(&mut self, enabled_tools: Option<Vec<String>>) {
+        self.enabled_tools = enabled_tools;
+    }
+
+    fn enabled_tools(&self) -> Option<Vec<String>> {
+        self.enabled_tools.clone()
+    }
 }
 
 impl AnthropicClient {
@@ -79,10 +89,11 @@ impl AnthropicClient {
             model,
             system_prompt,
             max_tokens,
+            enabled_tools: None,
         }
     }
 
-    fn send_request_and_handle_response(send_body: &Value) -> io::Result<String> {
+    fn send_request_and_handle_response(send_body: &Value) -> io::Result<(String, Option<TokenUsage>)> {
         let mut response = ureq::post(Self::api_url())
             .header("x-api-key", &Self::get_api_key()?)
             .header("anthropic-version", "2023-06-01")
@@ -106,7 +117,14 @@ impl AnthropicClient {
             None => "No response".to_string(),
         };
 
-        Ok(asd)
+        let usage = response.get("usage").and_then(|usage| {
+            Some(TokenUsage {
+                input_tokens: usage.get("input_tokens")?.as_u64()?,
+                output_tokens: usage.get("output_tokens")?.as_u64()?,
+            })
+        });
+
+        Ok((asd, usage))
     }
 
     fn api_url() -> String {
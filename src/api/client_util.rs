@@ -14,8 +14,21 @@
  * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
 
+use crate::estimate_token_count;
 use serde_json::Value;
 
+/// Messages that are shortened instead of dropped keep at least this many tokens
+const MIN_TRUNCATED_TOKENS: usize = 64;
+
+/// Summary of the history messages left out of a request
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct ContextFit {
+    pub(crate) dropped: usize,
+    pub(crate) truncated: usize,
+    /// The pinned messages alone exceed the budget
+    pub(crate) over_budget: bool,
+}
+
 pub(crate) fn create_messages(
     system_prompt: &str,
     context_content: &str,
@@ -43,11 +56,128 @@ pub(crate) fn create_messages(
     messages
 }
 
+/// Drop or shorten the oldest history messages until they fit the token budget.
+/// Pinned messages (zero-based indices) are never left out.
+pub(crate) fn fit_history_to_budget(
+    history_messages_json: &Value,
+    pinned: &[usize],
+    budget: usize,
+) -> (Value, ContextFit) {
+    let Some(messages) = history_messages_json.as_array() else {
+        return (history_messages_json.clone(), ContextFit::default());
+    };
+
+    let message_tokens = |message: &Value| {
+        estimate_token_count(message.get("content").and_then(Value::as_str).unwrap_or(""))
+    };
+
+    let mut total: usize = messages.iter().map(message_tokens).sum();
+    let mut fit = ContextFit::default();
+    let mut fitted = Vec::with_capacity(messages.len());
+
+    for (index, message) in messages.iter().enumerate() {
+        if total <= budget || pinned.contains(&index) {
+            fitted.push(message.clone());
+            continue;
+        }
+
+        let tokens = message_tokens(message);
+        let overflow = total - budget;
+
+        if tokens >= overflow + MIN_TRUNCATED_TOKENS {
+            // Keep the end of the message, which connects to the rest of the conversation
+            let content = message.get("content").and_then(Value::as_str).unwrap_or("");
+            // Two extra tokens cover the marker and rounding
+            let skip_chars = ((overflow + 2) * 4).min(content.chars().count());
+            let start = content
+                .char_indices()
+                .nth(skip_chars)
+                .map(|(i, _)| i)
+                .unwrap_or(content.len());
+
+            let mut shortened = message.clone();
+            shortened["content"] = Value::String(format!("[...] {}", &content[start..]));
+            total = total - tokens + message_tokens(&shortened);
+            fitted.push(shortened);
+            fit.truncated += 1;
+        } else {
+            total -= tokens;
+            fit.dropped += 1;
+        }
+    }
+
+    fit.over_budget = total > budget;
+    (Value::Array(fitted), fit)
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
 
-    use crate::api::client_util::create_messages;
+    use crate::api::client_util::{create_messages, fit_history_to_budget, ContextFit};
+
+    #[test]
+    fn test_fit_history_within_budget() {
+        let history = json!([
+            {"role": "user", "content": "Hello!"},
+            {"role": "assistant", "content": "Hi there!"}
+        ]);
+
+        let (fitted, fit) = fit_history_to_budget(&history, &[], 100);
+
+        assert_eq!(fitted, history);
+        assert_eq!(fit, ContextFit::default());
+    }
+
+    #[test]
+    fn test_fit_history_drops_oldest_unpinned() {
+        let long = "x".repeat(400); // 101 tokens
+        let history = json!([
+            {"role": "user", "content": long},
+            {"role": "assistant", "content": long},
+            {"role": "user", "content": long},
+            {"role": "assistant", "content": long}
+        ]);
+
+        let (fitted, fit) = fit_history_to_budget(&history, &[0], 250);
+
+        assert_eq!(fitted, json!([history[0], history[3]]));
+        assert_eq!(fit.dropped, 2);
+        assert_eq!(fit.truncated, 0);
+        assert!(!fit.over_budget);
+    }
+
+    #[test]
+    fn test_fit_history_truncates_large_message() {
+        let history = json!([
+            {"role": "user", "content": "a".repeat(2000)},
+            {"role": "assistant", "content": "Short answer"}
+        ]);
+
+        let (fitted, fit) = fit_history_to_budget(&history, &[], 300);
+
+        let shortened = fitted[0]["content"].as_str().unwrap();
+        assert!(shortened.starts_with("[...] "));
+        assert!(shortened.len() < 2000);
+        assert_eq!(fitted[1], history[1]);
+        assert_eq!(fit.truncated, 1);
+        assert_eq!(fit.dropped, 0);
+        assert!(!fit.over_budget);
+    }
+
+    #[test]
+    fn test_fit_history_pinned_over_budget() {
+        let history = json!([
+            {"role": "user", "content": "x".repeat(400)},
+            {"role": "assistant", "content": "Hi"}
+        ]);
+
+        let (fitted, fit) = fit_history_to_budget(&history, &[0], 10);
+
+        assert_eq!(fitted, json!([history[0]]));
+        assert_eq!(fit.dropped, 1);
+        assert!(fit.over_budget);
+    }
 
     #[test]
     fn test_create_messages_assistant() {
@@ -14,19 +14,29 @@
  * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
 use crate::api::{anthropic_client::AnthropicClient, ollama_client::OllamaClient};
+use crate::config::AppConfig;
 use serde::Deserialize;
 use serde_json::Value;
 use std::fmt::{Display, Formatter};
 use std::io;
 
 pub mod anthropic_client;
-mod client_util;
+pub(crate) mod client_util;
 pub mod ollama_client;
 
 #[derive(Deserialize, Debug)]
 pub struct ChatResponse {
     pub content: String,
     pub tool_calls: Option<Vec<ToolCall>>,
+    /// Token counts reported by the provider, if it reports them
+    #[serde(skip)]
+    pub usage: Option<TokenUsage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
 }
 
 #[derive(Deserialize, Debug)]
@@ -70,6 +80,11 @@ pub trait ChatClient {
     fn update_system_prompt(&mut self, system_prompt: String);
 
     fn system_prompt(&self) -> String;
+
+    /// Restrict the tools offered to the model by name, `None` enables all tools
+    fn update_enabled_tools(&mut self, enabled_tools: Option<Vec<String>>);
+
+    fn enabled_tools(&self) -> Option<Vec<String>>;
 }
 
 pub fn get_chat_client_implementation(
@@ -92,3 +107,19 @@ pub fn get_chat_client_implementation(
         _ => panic!("Unsupported provider"),
     }
 }
+
+/// Create a chat client for the active profile and model.
+/// The system prompt and tools of the previous client are kept.
+pub(crate) fn rebuild_chat_client(
+    chat_client: &dyn ChatClient,
+    app_config: &AppConfig,
+) -> Box<dyn ChatClient> {
+    let mut new_client = get_chat_client_implementation(
+        &app_config.current_profile.provider,
+        &app_config.current_model.model,
+        chat_client.system_prompt(),
+        app_config.user_config.max_tokens,
+    );
+    new_client.update_enabled_tools(chat_client.enabled_tools());
+    new_client
+}
@@ -19,7 +19,7 @@ use std::io;
 use std::process::Command;
 
 use crate::api::client_util::create_messages;
-use crate::api::{ChatClient, ChatResponse};
+use crate::api::{ChatClient, ChatResponse, TokenUsage};
 use crate::tool::tools::get_tools;
 
 static LLM_PROTOCOL: &str = "http";
@@ -36,6 +36,7 @@ struct ModelInformation {
 pub struct OllamaClient {
     pub(crate) system_prompt: String,
     model_information: ModelInformation,
+    enabled_tools: Option<Vec<String>>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -44,6 +45,28 @@ pub(crate) struct OllamaResponse {
     pub(crate) done: bool,
     pub(crate) done_reason: String,
     // pub(crate) error: Option<String>, TODO: Check if this can be used
+    #[serde(default)]
+    pub(crate) prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    pub(crate) eval_count: Option<u64>,
+}
+
+impl OllamaResponse {
+    /// The message with the token counts of the response
+    fn into_message(self) -> ChatResponse {
+        let usage = match (self.prompt_eval_count, self.eval_count) {
+            (Some(input_tokens), Some(output_tokens)) => Some(TokenUsage {
+                input_tokens,
+                output_tokens,
+            }),
+            _ => None,
+        };
+
+        ChatResponse {
+            usage,
+            ..self.message
+        }
+    }
 }
 
 impl ChatClient for OllamaClient {
@@ -61,10 +84,11 @@ impl ChatClient for OllamaClient {
             "system",
         );
 
-        let send_body = Self::build_json_body(&self.model_information, messages);
+        let send_body =
+            Self::build_json_body(&self.model_information, messages, self.enabled_tools.as_ref());
 
         let response = Self::poll_for_response(&send_body)?;
-        Ok(response.message)
+        Ok(response.into_message())
     }
 
     fn generate_tool_response(&self, tool_responses: Value) -> io::Result<ChatResponse> {
@@ -75,7 +99,7 @@ impl ChatClient for OllamaClient {
         });
 
         let response = Self::poll_for_response(&send_body)?;
-        Ok(response.message)
+        Ok(response.into_message())
     }
 
     fn model_context_size(&self) -> Option<usize> {
@@ -93,6 +117,14 @@ impl ChatClient for OllamaClient {
     fn system_prompt(&self) -> String {
         self.system_prompt.to_string()
     }
+
+    fn update_enabled_tools(&mut self, enabled_tools: Option<Vec<String>>) {
+        self.enabled_tools = enabled_tools;
+    }
+
+    fn enabled_tools(&self) -> Option<Vec<String>> {
+        self.enabled_tools.clone()
+    }
 }
 
 impl OllamaClient {
@@ -105,6 +137,7 @@ impl OllamaClient {
                 context_size: None,
                 supports_tools: false,
             },
+            enabled_tools: None,
         }
     }
 
@@ -169,7 +202,11 @@ impl OllamaClient {
         Ok(ollama_response)
     }
 
-    fn build_json_body(model_information: &ModelInformation, messages: Vec<Value>) -> Value {
+    fn build_json_body(
+        model_information: &ModelInformation,
+        messages: Vec<Value>,
+        enabled_tools: Option<&Vec<String>>,
+    ) -> Value {
         let mut base_body = serde_json::json!({
             "model": model_information.model,
             "messages": messages,
@@ -177,7 +214,10 @@ impl OllamaClient {
         });
 
         if model_information.supports_tools {
-            let tools = get_tools();
+            let tools: Vec<_> = get_tools()
+                .into_iter()
+                .filter(|tool| enabled_tools.is_none_or(|enabled| enabled.contains(&tool.name)))
+                .collect();
             base_body.as_object_mut().unwrap().insert(
                 "tools".to_string(),
                 serde_json::json!(
@@ -14,13 +14,14 @@
  * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
 use crate::command::commands::FileCommandDirectory;
+use crate::history_file::ChatIndex;
 use rustyline::completion::{Completer, FilenameCompleter, Pair};
 use rustyline::highlight::Highlighter;
 use rustyline::hint::Hinter;
 use rustyline::validate::{ValidationContext, ValidationResult, Validator};
 use rustyline::{Context, Helper};
 use std::borrow::Cow;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub struct CommandHelper {
     commands: Vec<(String, Option<String>)>,
@@ -46,6 +47,8 @@ impl CommandHelper {
 
 struct FileCompleter {
     base_dir: PathBuf,
+    /// Titles of chats in the data directory are shown next to the file names
+    index: ChatIndex,
     knowledge_dir: PathBuf,
     prompt_dir: PathBuf,
     filename_completer: FilenameCompleter,
@@ -57,8 +60,10 @@ impl FileCompleter {
         knowledge_dir: impl Into<PathBuf>,
         prompt_dir: impl Into<PathBuf>,
     ) -> Self {
+        let base_dir = base_dir.into();
         FileCompleter {
-            base_dir: base_dir.into(),
+            index: ChatIndex::load(&base_dir),
+            base_dir,
             knowledge_dir: knowledge_dir.into(),
             prompt_dir: prompt_dir.into(),
             filename_completer: FilenameCompleter::new(),
@@ -66,6 +71,21 @@ impl FileCompleter {
     }
 }
 
+impl FileCompleter {
+    fn with_titles(&self, pairs: Vec<Pair>) -> Vec<Pair> {
+        pairs
+            .into_iter()
+            .map(|pair| match self.index.get(Path::new(&pair.replacement)) {
+                Some(entry) => Pair {
+                    display: format!("{} ({})", pair.display, entry.title),
+                    replacement: pair.replacement,
+                },
+                None => pair,
+            })
+            .collect()
+    }
+}
+
 impl Completer for FileCompleter {
     type Candidate = Pair;
 
@@ -98,10 +118,15 @@ impl Completer for FileCompleter {
             // The cursor is at the end of the full path string now
             let pos = full_path_str.len();
 
-            return self.filename_completer.complete(&full_path_str, pos, ctx);
+            return self
+                .filename_completer
+                .complete(&full_path_str, pos, ctx)
+                .map(|(start, pairs)| (start, self.with_titles(pairs)));
         }
 
-        self.filename_completer.complete(line, line.len(), ctx)
+        self.filename_completer
+            .complete(line, line.len(), ctx)
+            .map(|(start, pairs)| (start, self.with_titles(pairs)))
     }
 }
 
@@ -133,11 +158,15 @@ impl Completer for CommandHelper {
                     }
                 }
 
-                let arg = parts.get(1).unwrap_or(&"");
+                // Complete the last argument, commands like `:context add` take several paths
+                let arg = match parts.last() {
+                    Some(arg) if parts.len() > 1 && !line.ends_with(' ') => *arg,
+                    _ => "",
+                };
                 let arg_start_pos = if arg.is_empty() {
                     line.len()
                 } else {
-                    line.find(arg).unwrap_or(line.len())
+                    line.rfind(arg).unwrap_or(line.len())
                 };
 
                 let res = self.file_completer.complete(arg, 0, ctx)?;
@@ -553,6 +582,32 @@ mod tests {
         assert!(completions.contains(&"hello @c/".to_string()));
     }
 
+    #[test]
+    fn test_complete_last_argument() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let knowledge_dir = temp_dir.path().display().to_string();
+        fs::write(temp_dir.path().join("notes.md"), b"content")?;
+
+        let helper = CommandHelper::new(
+            vec![("context".to_string(), None)],
+            vec![("context".to_string(), FileCommandDirectory::Knowledge)],
+            "",
+            &knowledge_dir,
+            "",
+        );
+        let history = DefaultHistory::new();
+        let ctx = Context::new(&history);
+
+        let line = format!(":context add a.md {knowledge_dir}/no");
+        let (pos, matches) = helper.complete(&line, line.len(), &ctx)?;
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].display, "notes.md");
+        assert_eq!(matches[0].replacement, format!("{knowledge_dir}/notes.md"));
+        assert_eq!(&line[..pos], ":context add a.md ");
+
+        Ok(())
+    }
+
     fn create_command_helper() -> CommandHelper {
         let commands = vec![
             ("help".to_string(), None),
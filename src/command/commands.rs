@@ -17,6 +17,7 @@
 use crate::api::ChatClient;
 use crate::command::commands_impl;
 use crate::config::profiles_config::ModelType;
+use crate::context::ContextChange;
 use crate::history_file::HistoryFile;
 use colored::Colorize;
 use std::collections::HashMap;
@@ -27,12 +28,18 @@ pub enum CommandResult {
     Continue,
     Quit,
     SwitchHistory(String),
-    SwitchContext(Option<PathBuf>),
+    UpdateContext(ContextChange),
     HandlePrompt(PathBuf, Option<String>),
     SwitchModel(ModelType),
     PrintModels,
     SwitchProfile(String),
     PrintProfiles,
+    Retry(Option<ModelType>),
+    SendPrompt(String),
+    Compact(usize),
+    SwitchTemporary,
+    /// History files that can be switched to with `#<number>`
+    SetResults(Vec<String>),
 }
 
 pub struct CommandParams<'a> {
@@ -126,6 +133,19 @@ pub(crate) fn create_command_registry<'a>(
         commands_impl::profile::command(&default_prefixes),
         commands_impl::tools::command(&default_prefixes),
         commands_impl::clear::command(&default_prefixes),
+        commands_impl::fork::command(&default_prefixes),
+        commands_impl::copy::command(&default_prefixes),
+        commands_impl::retry::command(&default_prefixes),
+        commands_impl::undo::command(&default_prefixes),
+        commands_impl::editlast::command(&default_prefixes),
+        commands_impl::compact::command(&default_prefixes),
+        commands_impl::pin::command(&default_prefixes),
+        commands_impl::unpin::command(&default_prefixes),
+        commands_impl::convert::command(&default_prefixes),
+        commands_impl::temp::command(&default_prefixes),
+        commands_impl::persist::command(&default_prefixes),
+        commands_impl::export::command(&default_prefixes),
+        commands_impl::search::command(&default_prefixes),
     ];
 
     let mut map: HashMap<String, CommandStruct<'a>> = HashMap::new();
@@ -156,7 +176,20 @@ mod tests {
         assert!(registry.contains_key("profile"));
         assert!(registry.contains_key("tools"));
         assert!(registry.contains_key("clear"));
-
-        assert_eq!(registry.len(), 12);
+        assert!(registry.contains_key("fork"));
+        assert!(registry.contains_key("copy"));
+        assert!(registry.contains_key("retry"));
+        assert!(registry.contains_key("undo"));
+        assert!(registry.contains_key("editlast"));
+        assert!(registry.contains_key("compact"));
+        assert!(registry.contains_key("pin"));
+        assert!(registry.contains_key("unpin"));
+        assert!(registry.contains_key("convert"));
+        assert!(registry.contains_key("temp"));
+        assert!(registry.contains_key("persist"));
+        assert!(registry.contains_key("export"));
+        assert!(registry.contains_key("search"));
+
+        assert_eq!(registry.len(), 25);
     }
 }
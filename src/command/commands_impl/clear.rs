@@ -16,7 +16,6 @@
 
 use crate::command::commands::{CommandParams, CommandResult, CommandStruct};
 use std::collections::HashMap;
-use std::fs::OpenOptions;
 use std::io;
 
 pub(crate) fn new<'a>(_default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
@@ -37,15 +36,9 @@ pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String
     new(default_prefixes)
 }
 
-/// Truncates the history file and reloads the in-memory content.
+/// Empties the history file, keeping only its front matter.
 pub(crate) fn clear_command(command_params: CommandParams) -> io::Result<CommandResult> {
-    let path = command_params.history.path.clone();
-    let _ = OpenOptions::new()
-        .write(true)
-        .truncate(true)
-        .open(&path)?;
-
-    command_params.history.reload_content();
+    command_params.history.clear()?;
 
     println!("History cleared: {}", command_params.history.filename);
 
@@ -63,7 +56,7 @@ mod tests {
     fn test_clear_command_empties_history() -> io::Result<()> {
         let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
 
-        history.append_user_input("Hello world")?;
+        history.append_user_input("Hello world", None)?;
         assert!(!history.get_content().is_empty());
 
         let params = CommandParams::new(vec![], &mut client, &mut history, dir_path.clone());
@@ -73,7 +66,7 @@ mod tests {
         assert_eq!(history.get_content(), "");
 
         let disk_content = fs::read_to_string(history.path.clone())?;
-        assert_eq!(disk_content, "");
+        assert_eq!(disk_content, "+++\nversion = 3\n+++\n");
 
         Ok(())
     }
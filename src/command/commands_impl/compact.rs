@@ -0,0 +1,82 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::command::commands::{CommandParams, CommandResult, CommandStruct};
+use std::collections::HashMap;
+use std::io;
+
+/// Number of most recent messages kept as they are when compacting
+pub(crate) const DEFAULT_KEEP_MESSAGES: usize = 4;
+
+pub(crate) fn new<'a>(_default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    (
+        "compact".to_string(),
+        CommandStruct::new(
+            "compact",
+            "Summarize older messages to free up context. The most recent messages are kept as they are.",
+            Some(":compact [keep <message count>]"),
+            None,
+            compact_command,
+            None,
+        ),
+    )
+}
+
+pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    new(default_prefixes)
+}
+
+pub(crate) fn compact_command(command_params: CommandParams) -> io::Result<CommandResult> {
+    let args = command_params.args;
+
+    match args.as_slice() {
+        [] => Ok(CommandResult::Compact(DEFAULT_KEEP_MESSAGES)),
+        [keyword, keep] if keyword == "keep" => match keep.parse::<usize>() {
+            Ok(keep) => Ok(CommandResult::Compact(keep)),
+            Err(_) => {
+                eprintln!("Invalid message count: {keep}");
+                Ok(CommandResult::Continue)
+            }
+        },
+        _ => {
+            eprintln!("Usage: :compact [keep <message count>]");
+            Ok(CommandResult::Continue)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::setup_test_environment;
+    use std::io;
+
+    fn run(args: &[&str]) -> io::Result<CommandResult> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        let args = args.iter().map(|s| s.to_string()).collect();
+        let params = CommandParams::new(args, &mut client, &mut history, dir_path);
+        compact_command(params)
+    }
+
+    #[test]
+    fn test_compact_command() -> io::Result<()> {
+        assert!(matches!(run(&[])?, CommandResult::Compact(DEFAULT_KEEP_MESSAGES)));
+        assert!(matches!(run(&["keep", "6"])?, CommandResult::Compact(6)));
+        assert!(matches!(run(&["keep", "many"])?, CommandResult::Continue));
+        assert!(matches!(run(&["6"])?, CommandResult::Continue));
+        Ok(())
+    }
+}
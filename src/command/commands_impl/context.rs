@@ -15,17 +15,19 @@
  */
 
 use crate::command::commands::{CommandParams, CommandResult, CommandStruct, FileCommandDirectory};
+use crate::context::{ContextChange, ContextEntry, ContextMode, ContextSource};
 use std::collections::HashMap;
 use std::io;
-use std::path::PathBuf;
 
 pub(crate) fn new<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
     (
         "context".to_string(),
         CommandStruct::new(
             "context",
-            "Set or unset current context file",
-            Some(":context <optional path>"),
+            "Manage the files, directories, globs and shell commands sent as context with prompts. \
+Entries are sent with each prompt (live), once or as a diff since they were last sent (diff). \
+Without arguments, the context is listed.",
+            Some(":context add|remove|list|clear|mode <paths> or :context !<shell command>"),
             Some(FileCommandDirectory::Knowledge),
             context_file_command,
             default_prefixes.get("context").cloned(),
@@ -38,10 +40,102 @@ pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String
 }
 
 pub(crate) fn context_file_command(command_params: CommandParams) -> io::Result<CommandResult> {
-    match command_params.args.first() {
-        Some(new_context_file) => Ok(CommandResult::SwitchContext(Some(PathBuf::from(
-            new_context_file,
-        )))),
-        _ => Ok(CommandResult::SwitchContext(None)),
+    let args = command_params.args;
+    let paths = |paths: &[String]| paths.iter().map(|path| ContextEntry::parse_path(path)).collect();
+    let joined = args.join(" ");
+    let (mode, rest) = ContextMode::split(&joined);
+
+    let change = match args.first().map(String::as_str) {
+        None | Some("list") => ContextChange::List,
+        Some("clear") => ContextChange::Clear,
+        // The rest of the line is the command, `:context !cargo test` or `:context diff:!cargo test`
+        Some(_) if rest.starts_with('!') => match rest[1..].trim() {
+            "" => {
+                println!("Usage: :context !<shell command>");
+                return Ok(CommandResult::Continue);
+            }
+            command => ContextChange::Add(vec![ContextEntry {
+                source: ContextSource::Command(command.to_string()),
+                mode,
+            }]),
+        },
+        Some("add") if args.len() > 1 => ContextChange::Add(paths(&args[1..])),
+        Some("remove") if args[1..].join(" ").starts_with('!') => {
+            ContextChange::Remove(vec![args[1..].join(" ")])
+        }
+        Some("remove") if args.len() > 1 => ContextChange::Remove(args[1..].to_vec()),
+        Some("mode") if args.len() > 2 => match args[args.len() - 1].parse() {
+            Ok(mode) => ContextChange::SetMode(args[1..args.len() - 1].join(" "), mode),
+            Err(e) => {
+                println!("{e}");
+                return Ok(CommandResult::Continue);
+            }
+        },
+        Some("add" | "remove") => {
+            println!("Usage: :context add|remove <paths>");
+            return Ok(CommandResult::Continue);
+        }
+        Some("mode") => {
+            println!("Usage: :context mode <number, path or !command> live|once|diff");
+            return Ok(CommandResult::Continue);
+        }
+        // A plain path replaces the context set
+        Some(_) => ContextChange::Set(paths(&args)),
+    };
+
+    Ok(CommandResult::UpdateContext(change))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::setup_test_environment;
+    use std::path::PathBuf;
+
+    fn run(input: &str) -> CommandResult {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        let args = input.split_whitespace().map(|s| s.to_string()).collect();
+        let params = CommandParams::new(args, &mut client, &mut history, dir_path);
+        context_file_command(params).unwrap()
+    }
+
+    fn parse(input: &str) -> ContextChange {
+        match run(input) {
+            CommandResult::UpdateContext(change) => change,
+            _ => panic!("Expected UpdateContext result but got something else"),
+        }
+    }
+
+    #[test]
+    fn test_context_command() {
+        let entry = |source, mode| ContextEntry { source, mode };
+        let path = |path: &str, mode| entry(ContextSource::Path(PathBuf::from(path)), mode);
+        let command = |command: &str, mode| entry(ContextSource::Command(command.to_string()), mode);
+
+        assert!(matches!(parse(""), ContextChange::List));
+        assert!(matches!(parse("clear"), ContextChange::Clear));
+        assert!(matches!(
+            parse("add a.md diff:src"),
+            ContextChange::Add(entries) if entries == [path("a.md", ContextMode::Live), path("src", ContextMode::Diff)]
+        ));
+        assert!(matches!(
+            parse("once:notes.md"),
+            ContextChange::Set(entries) if entries == [path("notes.md", ContextMode::Once)]
+        ));
+        assert!(matches!(
+            parse("!git log  -5"),
+            ContextChange::Add(entries) if entries == [command("git log -5", ContextMode::Live)]
+        ));
+        assert!(matches!(
+            parse("diff:! cargo test"),
+            ContextChange::Add(entries) if entries == [command("cargo test", ContextMode::Diff)]
+        ));
+        assert!(matches!(parse("remove 2 a.md"), ContextChange::Remove(entries) if entries == ["2", "a.md"]));
+        assert!(matches!(parse("remove !git log -5"), ContextChange::Remove(entries) if entries == ["!git log -5"]));
+        assert!(matches!(
+            parse("mode !cargo test diff"),
+            ContextChange::SetMode(entry, ContextMode::Diff) if entry == "!cargo test"
+        ));
+        assert!(matches!(run("mode 1 sometimes"), CommandResult::Continue));
     }
 }
@@ -0,0 +1,103 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::command::commands::{CommandParams, CommandResult, CommandStruct, FileCommandDirectory};
+use crate::history_file::HistoryFile;
+use std::collections::HashMap;
+use std::io;
+
+pub(crate) fn new<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    (
+        "convert".to_string(),
+        CommandStruct::new(
+            "convert",
+            "Convert the current history file to another format, chosen by the destination's extension (.jsonl for JSON lines, otherwise text).",
+            Some(":convert <destination>"),
+            Some(FileCommandDirectory::Cforge),
+            convert_command,
+            default_prefixes.get("convert").cloned(),
+        ),
+    )
+}
+
+pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    new(default_prefixes)
+}
+
+pub(crate) fn convert_command(command_params: CommandParams) -> io::Result<CommandResult> {
+    let Some(destination) = command_params.args.first() else {
+        eprintln!("Error: No destination specified. Usage: :convert <destination>");
+        return Ok(CommandResult::Continue);
+    };
+
+    let destination = HistoryFile::resolve_path(destination, &command_params.cforge_dir);
+    match command_params.history.convert(&destination) {
+        Ok(()) => println!(
+            "Converted {} to {}",
+            command_params.history.filename,
+            destination.display()
+        ),
+        Err(e) => eprintln!("Error converting history file: {e}"),
+    }
+
+    Ok(CommandResult::Continue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::setup_test_environment;
+    use std::{fs, io};
+
+    #[test]
+    fn test_convert_command_both_ways() -> io::Result<()> {
+        colored::control::set_override(false);
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        history.append_ai_response("Answer", None)?;
+        let expected_json = history.get_content_json()?;
+
+        let params = CommandParams::new(vec!["chat.jsonl".to_string()], &mut client, &mut history, dir_path.clone());
+        convert_command(params)?;
+
+        let jsonl_path = format!("{dir_path}/chat.jsonl");
+        let lines: Vec<String> = fs::read_to_string(&jsonl_path)?.lines().map(String::from).collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], r#"{"role":"user","content":"Test conversation content"}"#);
+        assert_eq!(lines[2], r#"{"role":"assistant","content":"Answer"}"#);
+
+        let mut jsonl_history = HistoryFile::new(jsonl_path, dir_path.clone())?;
+        assert_eq!(jsonl_history.get_content_json()?, expected_json);
+
+        let params = CommandParams::new(vec!["back.txt".to_string()], &mut client, &mut jsonl_history, dir_path.clone());
+        convert_command(params)?;
+
+        let text_history = HistoryFile::new("back.txt".to_string(), dir_path)?;
+        assert_eq!(text_history.get_content_json()?, expected_json);
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_command_existing_destination() -> io::Result<()> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        fs::write(format!("{dir_path}/existing.jsonl"), "{}")?;
+
+        let params = CommandParams::new(vec!["existing.jsonl".to_string()], &mut client, &mut history, dir_path.clone());
+        convert_command(params)?;
+
+        assert_eq!(fs::read_to_string(format!("{dir_path}/existing.jsonl"))?, "{}");
+        Ok(())
+    }
+}
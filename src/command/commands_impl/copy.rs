@@ -0,0 +1,103 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::command::commands::{CommandParams, CommandResult, CommandStruct, FileCommandDirectory};
+use crate::history_file::HistoryFile;
+use std::collections::HashMap;
+use std::{fs, io};
+
+pub(crate) fn new<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    (
+        "copy".to_string(),
+        CommandStruct::new(
+            "copy",
+            "Copy the current history file to another location. Either relative to the data directory or absolute path.",
+            Some(":copy <destination>"),
+            Some(FileCommandDirectory::Cforge),
+            copy_command,
+            default_prefixes.get("copy").cloned(),
+        ),
+    )
+}
+
+pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    new(default_prefixes)
+}
+
+pub(crate) fn copy_command(command_params: CommandParams) -> io::Result<CommandResult> {
+    let Some(destination) = command_params.args.first() else {
+        eprintln!("Error: No destination specified. Usage: :copy <destination>");
+        return Ok(CommandResult::Continue);
+    };
+
+    let destination = HistoryFile::resolve_path(destination, &command_params.cforge_dir);
+    if destination.exists() {
+        eprintln!("Error: {} already exists", destination.display());
+        return Ok(CommandResult::Continue);
+    }
+
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Temporary chats are written out in the destination's format
+    if command_params.history.is_temporary() {
+        command_params.history.convert(&destination)?;
+    } else {
+        fs::copy(&command_params.history.path, &destination)?;
+    }
+    println!(
+        "Copied {} to {}",
+        command_params.history.filename,
+        destination.display()
+    );
+
+    Ok(CommandResult::Continue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::setup_test_environment;
+    use std::{fs, io};
+
+    #[test]
+    fn test_copy_command() -> io::Result<()> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        let args = vec!["nested/copy.txt".to_string()];
+        let params = CommandParams::new(args, &mut client, &mut history, dir_path.clone());
+        let result = copy_command(params)?;
+
+        assert!(matches!(result, CommandResult::Continue));
+        assert_eq!(
+            fs::read_to_string(format!("{}/nested/copy.txt", dir_path))?,
+            fs::read_to_string(&history.path)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_command_existing_destination() -> io::Result<()> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        fs::write(format!("{}/copy.txt", dir_path), "Existing content")?;
+        let args = vec!["copy.txt".to_string()];
+        let params = CommandParams::new(args, &mut client, &mut history, dir_path.clone());
+        copy_command(params)?;
+
+        assert_eq!(fs::read_to_string(format!("{}/copy.txt", dir_path))?, "Existing content");
+        Ok(())
+    }
+}
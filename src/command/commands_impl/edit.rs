@@ -33,6 +33,11 @@ pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String
 
 pub(crate) fn edit_command(command_params: CommandParams) -> io::Result<CommandResult> {
     let history = command_params.history;
+    if history.is_temporary() {
+        eprintln!("Error: A temporary chat can't be edited, use :persist <history file> to save it first");
+        return Ok(CommandResult::Continue);
+    }
+
     let editor = get_editor();
 
     let status = Command::new(editor).arg(history.path.clone()).status();
@@ -0,0 +1,89 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::command::command_util::get_editor;
+use crate::command::commands::{CommandParams, CommandResult, CommandStruct};
+use std::collections::HashMap;
+use std::process::Command;
+use std::{fs, io};
+
+pub(crate) fn new<'a>(_default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    (
+        "editlast".to_string(),
+        CommandStruct::new(
+            "editlast",
+            "Edit the last prompt in your editor and send it again. Everything after the prompt is removed.",
+            None,
+            None,
+            editlast_command,
+            None,
+        ),
+    )
+}
+
+pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    new(default_prefixes)
+}
+
+pub(crate) fn editlast_command(command_params: CommandParams) -> io::Result<CommandResult> {
+    let history = command_params.history;
+
+    let Some((message_number, message)) = history.last_user_message()? else {
+        println!("No prompt to edit");
+        return Ok(CommandResult::Continue);
+    };
+
+    let temp_file = tempfile::Builder::new().suffix(".md").tempfile()?;
+    fs::write(temp_file.path(), &message)?;
+
+    let status = Command::new(get_editor()).arg(temp_file.path()).status();
+    if !status.is_ok_and(|s| s.success()) {
+        eprintln!("Error opening file in editor");
+        return Ok(CommandResult::Continue);
+    }
+
+    let edited = fs::read_to_string(temp_file.path())?.trim().to_string();
+    if edited.is_empty() {
+        println!("Edited prompt is empty, nothing was sent");
+        return Ok(CommandResult::Continue);
+    }
+
+    history.truncate_messages(message_number - 1)?;
+    Ok(CommandResult::SendPrompt(edited))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::setup_test_environment;
+    use std::{env, io};
+
+    #[test]
+    fn test_editlast_command() -> io::Result<()> {
+        colored::control::set_override(false);
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        unsafe { env::set_var("EDITOR", "echo"); }
+        history.append_user_input("Question", None)?;
+        history.append_ai_response("Answer", None)?;
+
+        let params = CommandParams::new(vec![], &mut client, &mut history, dir_path);
+        let result = editlast_command(params)?;
+
+        assert!(matches!(result, CommandResult::SendPrompt(prompt) if prompt == "Question"));
+        assert_eq!(history.get_content(), "Test conversation content");
+        Ok(())
+    }
+}
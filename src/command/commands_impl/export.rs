@@ -0,0 +1,99 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::command::commands::{CommandParams, CommandResult, CommandStruct, FileCommandDirectory};
+use crate::history_file::{write_export, ExportFormat, HistoryFile};
+use std::collections::HashMap;
+use std::io;
+
+pub(crate) fn new<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    (
+        "export".to_string(),
+        CommandStruct::new(
+            "export",
+            "Export the current chat to Markdown, HTML or JSON for sharing. Either relative to the data directory or absolute path.",
+            Some(":export <md|html|json> <destination>"),
+            Some(FileCommandDirectory::Cforge),
+            export_command,
+            default_prefixes.get("export").cloned(),
+        ),
+    )
+}
+
+pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    new(default_prefixes)
+}
+
+pub(crate) fn export_command(command_params: CommandParams) -> io::Result<CommandResult> {
+    let [format, destination] = command_params.args.as_slice() else {
+        eprintln!("Error: Usage: :export <md|html|json> <destination>");
+        return Ok(CommandResult::Continue);
+    };
+
+    let format = match ExportFormat::parse_export_format(format) {
+        Ok(format) => format,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            return Ok(CommandResult::Continue);
+        }
+    };
+
+    let history = command_params.history;
+    let destination = HistoryFile::resolve_path(destination, &command_params.cforge_dir);
+    let content = format.render(&history.filename, &history.stored_content_json()?)?;
+
+    match write_export(&destination, &content) {
+        Ok(()) => println!("Exported {} to {}", history.filename, destination.display()),
+        Err(e) => eprintln!("Error exporting chat: {e}"),
+    }
+
+    Ok(CommandResult::Continue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::setup_test_environment;
+    use std::{fs, io};
+
+    #[test]
+    fn test_export_command() -> io::Result<()> {
+        colored::control::set_override(false);
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        history.append_ai_response("Answer", None)?;
+
+        let args = vec!["md".to_string(), "shared/chat.md".to_string()];
+        let params = CommandParams::new(args, &mut client, &mut history, dir_path.clone());
+        let result = export_command(params)?;
+        assert!(matches!(result, CommandResult::Continue));
+
+        assert_eq!(
+            fs::read_to_string(format!("{dir_path}/shared/chat.md"))?,
+            "# test-history.txt\n\n## User\n\nTest conversation content\n\n## Assistant\n\nAnswer\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_command_invalid_format() -> io::Result<()> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        let args = vec!["pdf".to_string(), "chat.pdf".to_string()];
+        let params = CommandParams::new(args, &mut client, &mut history, dir_path.clone());
+        export_command(params)?;
+        assert!(fs::metadata(format!("{dir_path}/chat.pdf")).is_err());
+        Ok(())
+    }
+}
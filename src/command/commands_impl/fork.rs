@@ -0,0 +1,170 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::command::commands::{CommandParams, CommandResult, CommandStruct, FileCommandDirectory};
+use crate::history_file::HistoryFile;
+use std::collections::HashMap;
+use std::io;
+
+pub(crate) fn new<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    (
+        "fork".to_string(),
+        CommandStruct::new(
+            "fork",
+            "Copy the current chat up to an optional message number into a new history file and switch to it.",
+            Some(":fork <new history file> <optional 'at' message number>"),
+            Some(FileCommandDirectory::Cforge),
+            fork_command,
+            default_prefixes.get("fork").cloned(),
+        ),
+    )
+}
+
+pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    new(default_prefixes)
+}
+
+pub(crate) fn fork_command(command_params: CommandParams) -> io::Result<CommandResult> {
+    let Some(new_history_file) = command_params.args.first() else {
+        eprintln!("Error: No history file specified. Usage: :fork <new history file> <optional 'at' message number>");
+        return Ok(CommandResult::Continue);
+    };
+
+    let at_message = match &command_params.args[1..] {
+        [] => None,
+        [number] => Some(number),
+        [at, number] if at.eq_ignore_ascii_case("at") => Some(number),
+        _ => {
+            eprintln!("Error: Usage: :fork <new history file> <optional 'at' message number>");
+            return Ok(CommandResult::Continue);
+        }
+    };
+
+    let history = command_params.history;
+    if history.is_temporary() {
+        eprintln!("Error: A temporary chat can't be forked, use :persist <history file> to save it first");
+        return Ok(CommandResult::Continue);
+    }
+
+    let message_number = match at_message {
+        None => history.message_count()?,
+        Some(number) => match number.parse::<usize>() {
+            Ok(n) if n > 0 => n,
+            _ => {
+                eprintln!("Error: Invalid message number: {number}");
+                return Ok(CommandResult::Continue);
+            }
+        },
+    };
+
+    let content = match history.content_until_message(message_number) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            return Ok(CommandResult::Continue);
+        }
+    };
+
+    // The fork starts with the same session settings as its parent
+    let mut child_front_matter = history.front_matter().cloned().unwrap_or_default();
+    child_front_matter.parent = Some(history.path.clone());
+    child_front_matter.forked_at = Some(message_number);
+    child_front_matter.children.clear();
+    child_front_matter.pinned.retain(|&pinned| pinned <= message_number);
+
+    let child = match HistoryFile::create(
+        new_history_file.clone(),
+        command_params.cforge_dir,
+        Some(child_front_matter),
+        &content,
+    ) {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("Error creating fork: {e}");
+            return Ok(CommandResult::Continue);
+        }
+    };
+
+    let mut parent_front_matter = history.front_matter().cloned().unwrap_or_default();
+    parent_front_matter.children.push(child.path.clone());
+    history.update_front_matter(parent_front_matter)?;
+
+    println!(
+        "Forked {} at message {message_number} into {}",
+        history.filename, child.filename
+    );
+
+    Ok(CommandResult::SwitchHistory(child.path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::setup_test_environment;
+    use std::{fs, io};
+
+    #[test]
+    fn test_fork_command() -> io::Result<()> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        history.append_user_input("Second message", None)?;
+
+        let args = vec!["fork.txt".to_string(), "at".to_string(), "1".to_string()];
+        let params = CommandParams::new(args, &mut client, &mut history, dir_path.clone());
+        let result = fork_command(params)?;
+
+        let child_path = format!("{}/fork.txt", dir_path);
+        if let CommandResult::SwitchHistory(path) = result {
+            assert_eq!(path, child_path);
+        } else {
+            panic!("Expected SwitchHistory result but got something else");
+        }
+
+        let child = HistoryFile::new(child_path.clone(), dir_path)?;
+        assert_eq!(child.get_content(), "Test conversation content");
+        let child_front_matter = child.front_matter().unwrap();
+        assert_eq!(child_front_matter.parent, Some(history.path.clone()));
+        assert_eq!(child_front_matter.forked_at, Some(1));
+
+        assert_eq!(history.front_matter().unwrap().children, vec![child_path]);
+        assert!(fs::read_to_string(&history.path)?.contains("Second message"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fork_command_invalid_message_number() -> io::Result<()> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        let args = vec!["fork.txt".to_string(), "at".to_string(), "5".to_string()];
+        let params = CommandParams::new(args, &mut client, &mut history, dir_path.clone());
+        let result = fork_command(params)?;
+
+        assert!(matches!(result, CommandResult::Continue));
+        assert!(!fs::exists(format!("{}/fork.txt", dir_path))?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fork_command_existing_file() -> io::Result<()> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        fs::write(format!("{}/fork.txt", dir_path), "Existing content")?;
+        let args = vec!["fork.txt".to_string()];
+        let params = CommandParams::new(args, &mut client, &mut history, dir_path.clone());
+        let result = fork_command(params)?;
+
+        assert!(matches!(result, CommandResult::Continue));
+        assert_eq!(fs::read_to_string(format!("{}/fork.txt", dir_path))?, "Existing content");
+        Ok(())
+    }
+}
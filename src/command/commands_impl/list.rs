@@ -15,17 +15,38 @@
  */
 
 use crate::command::commands::{CommandParams, CommandResult, CommandStruct, FileCommandDirectory};
+use crate::history_file::{history_files, ChatIndex, ChatInfo};
 use std::collections::HashMap;
-use std::fs;
 use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Order of the listed chats
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ListSort {
+    /// Most recently modified first
+    Recent,
+    /// Largest first
+    Size,
+    Name,
+}
+
+#[derive(Debug, PartialEq)]
+struct ListOptions {
+    pattern: String,
+    sort: ListSort,
+    limit: Option<usize>,
+}
+
+const USAGE: &str = ":list <optional pattern> <optional --sort recent|size|name> <optional --limit N>";
 
 pub(crate) fn new<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
     (
         "list".to_string(),
         CommandStruct::new(
             "list",
-            "List files in the cforge directory. Optionally, you can provide a pattern to filter the results.",
-            Some(":list <optional pattern>"),
+            "List chats in the cforge directory with their title, message count, estimated tokens and last change. Optionally, you can provide a pattern to filter the results. Switch to a chat with :switch #<number>.",
+            Some(USAGE),
             Some(FileCommandDirectory::Cforge),
             list_command,
             default_prefixes.get("list").cloned(),
@@ -38,39 +59,153 @@ pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String
 }
 
 pub(crate) fn list_command(command_params: CommandParams) -> io::Result<CommandResult> {
-    let empty_string = String::from("");
-    let pattern = command_params.args.first().unwrap_or(&empty_string);
-
-    fn list_dir_contents(dir: &str, pattern: &str, cforge_dir: &str) -> io::Result<()> {
-        for entry in fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            if (pattern.is_empty() || path.display().to_string().contains(pattern))
-                && !path.is_dir()
-            {
-                match path.display().to_string().strip_prefix(cforge_dir) {
-                    None => println!("{}", path.display()),
-                    Some(ds) => {
-                        let mut cleaned_ds = ds.to_string();
-                        if cleaned_ds.starts_with('/') {
-                            cleaned_ds = cleaned_ds[1..].to_string();
-                        }
-                        println!("{cleaned_ds}")
-                    }
+    let options = match parse_options(&command_params.args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Error: {e}. Usage: {USAGE}");
+            return Ok(CommandResult::Continue);
+        }
+    };
+
+    let cforge_dir = &command_params.cforge_dir.clone();
+    let index = ChatIndex::load(Path::new(cforge_dir));
+    let mut chats: Vec<ChatInfo> = history_files(Path::new(cforge_dir))?
+        .iter()
+        .filter(|path| options.pattern.is_empty() || path.display().to_string().contains(&options.pattern))
+        .map(|path| ChatInfo::read(path, &index))
+        .collect();
+
+    match options.sort {
+        ListSort::Recent => chats.sort_by_key(|chat| std::cmp::Reverse(chat.modified)),
+        ListSort::Size => chats.sort_by_key(|chat| std::cmp::Reverse(chat.size)),
+        ListSort::Name => chats.sort_by(|a, b| a.path.cmp(&b.path)),
+    }
+    if let Some(limit) = options.limit {
+        chats.truncate(limit);
+    }
+
+    let files: Vec<PathBuf> = chats.iter().map(|chat| chat.path.clone()).collect();
+    let parents: HashMap<PathBuf, PathBuf> = chats
+        .iter()
+        .filter_map(|chat| chat.parent.clone().map(|parent| (chat.path.clone(), parent)))
+        .collect();
+
+    let ordered = tree_order(&files, &parents);
+    let now = SystemTime::now();
+    let names: Vec<String> = ordered
+        .iter()
+        .map(|(depth, index)| tree_name(&files[*index], *depth, cforge_dir))
+        .collect();
+    let name_width = names.iter().map(|name| name.chars().count()).max().unwrap_or_default();
+
+    for (number, ((_, index), name)) in ordered.iter().zip(&names).enumerate() {
+        let chat = &chats[*index];
+        println!(
+            "{:>4} {name:<name_width$} {:>5} msgs {:>7} tokens  {:<14} {}",
+            format!("#{}", number + 1),
+            chat.message_count,
+            chat.tokens,
+            chat.modified.map(|modified| format_age(modified, now)).unwrap_or_default(),
+            chat.title.as_deref().unwrap_or_default()
+        );
+    }
+
+    Ok(CommandResult::SetResults(
+        ordered
+            .iter()
+            .map(|(_, index)| files[*index].display().to_string())
+            .collect(),
+    ))
+}
+
+fn parse_options(args: &[String]) -> Result<ListOptions, String> {
+    let mut options = ListOptions {
+        pattern: String::new(),
+        sort: ListSort::Name,
+        limit: None,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--sort" => {
+                options.sort = match args.next().map(|sort| sort.to_lowercase()).as_deref() {
+                    Some("recent") => ListSort::Recent,
+                    Some("size") => ListSort::Size,
+                    Some("name") => ListSort::Name,
+                    _ => return Err("Expected recent, size or name after --sort".to_string()),
                 }
             }
-            if path.is_dir() {
-                list_dir_contents(path.to_str().unwrap(), pattern, cforge_dir)?;
+            "--limit" => {
+                options.limit = match args.next().map(|limit| limit.parse::<usize>()) {
+                    Some(Ok(limit)) => Some(limit),
+                    _ => return Err("Expected a number after --limit".to_string()),
+                }
             }
+            pattern if options.pattern.is_empty() => options.pattern = pattern.to_string(),
+            _ => return Err(format!("Unexpected argument: {arg}")),
         }
-        Ok(())
     }
 
-    let cforge_dir = &command_params.cforge_dir.clone();
-    list_dir_contents(cforge_dir, pattern, cforge_dir)?;
+    Ok(options)
+}
+
+/// Order the files so that forks are listed and indented under their parent.
+/// Files whose parent is not listed are shown at the top level.
+/// Returns the depth and the index of each file.
+fn tree_order(files: &[PathBuf], parents: &HashMap<PathBuf, PathBuf>) -> Vec<(usize, usize)> {
+    fn add_children(
+        index: usize,
+        depth: usize,
+        files: &[PathBuf],
+        parents: &HashMap<PathBuf, PathBuf>,
+        order: &mut Vec<(usize, usize)>,
+    ) {
+        order.push((depth, index));
+
+        for (child, _) in files
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| parents.get(*f) == Some(&files[index]))
+        {
+            add_children(child, depth + 1, files, parents, order);
+        }
+    }
+
+    let mut order = vec![];
+    for (index, file) in files.iter().enumerate() {
+        if parents.get(file).is_none_or(|parent| !files.contains(parent)) {
+            add_children(index, 0, files, parents, &mut order);
+        }
+    }
+    order
+}
+
+fn tree_name(file: &Path, depth: usize, cforge_dir: &str) -> String {
+    let display = display_path(file, cforge_dir);
+    if depth == 0 {
+        display
+    } else {
+        format!("{}└─ {display}", "   ".repeat(depth - 1))
+    }
+}
+
+fn display_path(path: &Path, cforge_dir: &str) -> String {
+    match path.display().to_string().strip_prefix(cforge_dir) {
+        None => path.display().to_string(),
+        Some(ds) => ds.strip_prefix('/').unwrap_or(ds).to_string(),
+    }
+}
 
-    Ok(CommandResult::Continue)
+/// Time since the last change, e.g. `5 min ago`
+fn format_age(modified: SystemTime, now: SystemTime) -> String {
+    let seconds = now.duration_since(modified).unwrap_or_default().as_secs();
+    match seconds {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{} min ago", seconds / 60),
+        3600..86_400 => format!("{} h ago", seconds / 3600),
+        _ => format!("{} days ago", seconds / 86_400),
+    }
 }
 
 #[cfg(test)]
@@ -84,9 +219,13 @@ mod tests {
         let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
         fs::write(format!("{}/history1.txt", dir_path), "Content 1")?;
         fs::write(format!("{}/history2.txt", dir_path), "Content 2")?;
-        let params = CommandParams::new(vec![], &mut client, &mut history, dir_path);
+        let params = CommandParams::new(vec![], &mut client, &mut history, dir_path.clone());
         let result = list_command(params)?;
-        assert!(matches!(result, CommandResult::Continue));
+        assert!(matches!(result, CommandResult::SetResults(results) if results == vec![
+            format!("{dir_path}/history1.txt"),
+            format!("{dir_path}/history2.txt"),
+            format!("{dir_path}/test-history.txt"),
+        ]));
         Ok(())
     }
 
@@ -97,9 +236,85 @@ mod tests {
         fs::write(format!("{}/history2.txt", dir_path), "Content 2")?;
         fs::write(format!("{}/other.txt", dir_path), "Other content")?;
         let args = vec!["history".to_string()];
-        let params = CommandParams::new(args, &mut client, &mut history, dir_path);
+        let params = CommandParams::new(args, &mut client, &mut history, dir_path.clone());
+        let result = list_command(params)?;
+        assert!(matches!(result, CommandResult::SetResults(results) if results.len() == 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_command_sort_and_limit() -> io::Result<()> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        fs::write(format!("{}/large.txt", dir_path), "Content ".repeat(100))?;
+        let args = ["--sort", "size", "--limit", "1"].map(String::from).to_vec();
+        let params = CommandParams::new(args, &mut client, &mut history, dir_path.clone());
         let result = list_command(params)?;
-        assert!(matches!(result, CommandResult::Continue));
+        assert!(matches!(result, CommandResult::SetResults(results) if results == vec![format!("{dir_path}/large.txt")]));
         Ok(())
     }
+
+    #[test]
+    fn test_parse_options() {
+        let args = ["chat", "--limit", "5", "--sort", "Recent"].map(String::from);
+        assert_eq!(
+            parse_options(&args),
+            Ok(ListOptions {
+                pattern: "chat".to_string(),
+                sort: ListSort::Recent,
+                limit: Some(5),
+            })
+        );
+
+        assert!(parse_options(&["--sort".to_string(), "age".to_string()]).is_err());
+        assert!(parse_options(&["--limit".to_string()]).is_err());
+        assert!(parse_options(&["one".to_string(), "two".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_tree_order() {
+        let dir = "/data";
+        let parent = PathBuf::from("/data/parent.md");
+        let child = PathBuf::from("/data/child.md");
+        let grandchild = PathBuf::from("/data/nested/grandchild.md");
+        let other = PathBuf::from("/data/other.md");
+        let orphan = PathBuf::from("/data/orphan.md");
+
+        let files = vec![
+            child.clone(),
+            grandchild.clone(),
+            other.clone(),
+            parent.clone(),
+            orphan.clone(),
+        ];
+        let parents = HashMap::from([
+            (child.clone(), parent.clone()),
+            (grandchild.clone(), child.clone()),
+            (orphan.clone(), PathBuf::from("/data/deleted.md")),
+        ]);
+
+        let lines: Vec<String> = tree_order(&files, &parents)
+            .into_iter()
+            .map(|(depth, index)| tree_name(&files[index], depth, dir))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "other.md",
+                "parent.md",
+                "└─ child.md",
+                "   └─ nested/grandchild.md",
+                "orphan.md",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_age() {
+        let now = SystemTime::now();
+        let ago = |seconds| now - std::time::Duration::from_secs(seconds);
+        assert_eq!(format_age(ago(5), now), "just now");
+        assert_eq!(format_age(ago(300), now), "5 min ago");
+        assert_eq!(format_age(ago(7200), now), "2 h ago");
+        assert_eq!(format_age(ago(3 * 86_400), now), "3 days ago");
+    }
 }
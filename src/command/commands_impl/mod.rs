@@ -27,3 +27,16 @@ pub(crate) mod edit;
 pub(crate) mod sysprompt;
 pub(crate) mod context;
 pub(crate) mod clear;
+pub(crate) mod fork;
+pub(crate) mod copy;
+pub(crate) mod retry;
+pub(crate) mod undo;
+pub(crate) mod editlast;
+pub(crate) mod compact;
+pub(crate) mod pin;
+pub(crate) mod unpin;
+pub(crate) mod convert;
+pub(crate) mod temp;
+pub(crate) mod persist;
+pub(crate) mod export;
+pub(crate) mod search;
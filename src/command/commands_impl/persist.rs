@@ -0,0 +1,97 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::command::commands::{CommandParams, CommandResult, CommandStruct, FileCommandDirectory};
+use crate::history_file::HistoryFile;
+use std::collections::HashMap;
+use std::io;
+
+pub(crate) fn new<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    (
+        "persist".to_string(),
+        CommandStruct::new(
+            "persist",
+            "Save the temporary chat to a history file and switch to it. Either relative to the data directory or absolute path.",
+            Some(":persist <history file>"),
+            Some(FileCommandDirectory::Cforge),
+            persist_command,
+            default_prefixes.get("persist").cloned(),
+        ),
+    )
+}
+
+pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    new(default_prefixes)
+}
+
+pub(crate) fn persist_command(command_params: CommandParams) -> io::Result<CommandResult> {
+    let Some(history_file) = command_params.args.first() else {
+        eprintln!("Error: No history file specified. Usage: :persist <history file>");
+        return Ok(CommandResult::Continue);
+    };
+
+    if !command_params.history.is_temporary() {
+        eprintln!(
+            "Error: {} is already saved, use :copy to save a copy of it",
+            command_params.history.filename
+        );
+        return Ok(CommandResult::Continue);
+    }
+
+    let destination = HistoryFile::resolve_path(history_file, &command_params.cforge_dir);
+    if let Err(e) = command_params.history.convert(&destination) {
+        eprintln!("Error saving temporary chat: {e}");
+        return Ok(CommandResult::Continue);
+    }
+
+    println!("Saved the temporary chat to {}", destination.display());
+    Ok(CommandResult::SwitchHistory(history_file.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{make_mock_client, setup_test_environment};
+    use std::{fs, io};
+
+    #[test]
+    fn test_persist_command() -> io::Result<()> {
+        colored::control::set_override(false);
+        let temp_dir = tempfile::tempdir()?;
+        let dir_path = temp_dir.path().display().to_string();
+        let mut client = make_mock_client();
+        let mut history = HistoryFile::temporary();
+        history.append_user_input("Worth keeping", None)?;
+
+        let params = CommandParams::new(vec!["kept.txt".to_string()], &mut client, &mut history, dir_path.clone());
+        let result = persist_command(params)?;
+        assert!(matches!(result, CommandResult::SwitchHistory(ref path) if path == "kept.txt"));
+
+        let persisted = HistoryFile::new("kept.txt".to_string(), dir_path)?;
+        assert_eq!(persisted.get_content_json()?, history.get_content_json()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_persist_command_saved_chat() -> io::Result<()> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        let params = CommandParams::new(vec!["kept.txt".to_string()], &mut client, &mut history, dir_path.clone());
+        let result = persist_command(params)?;
+        assert!(matches!(result, CommandResult::Continue));
+        assert!(fs::metadata(format!("{dir_path}/kept.txt")).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::command::commands::{CommandParams, CommandResult, CommandStruct};
+use std::collections::HashMap;
+use std::io;
+
+pub(crate) fn new<'a>(_default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    (
+        "pin".to_string(),
+        CommandStruct::new(
+            "pin",
+            "Pin a message so that it is always sent, even when older messages no longer fit the context. Without a message number, the pinned messages are listed.",
+            Some(":pin <optional message number>"),
+            None,
+            pin_command,
+            None,
+        ),
+    )
+}
+
+pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    new(default_prefixes)
+}
+
+pub(crate) fn pin_command(command_params: CommandParams) -> io::Result<CommandResult> {
+    let history = command_params.history;
+    let mut front_matter = history.front_matter().cloned().unwrap_or_default();
+
+    let Some(number) = command_params.args.first() else {
+        if front_matter.pinned.is_empty() {
+            println!("No pinned messages");
+        } else {
+            let pinned: Vec<String> = front_matter.pinned.iter().map(|n| n.to_string()).collect();
+            println!("Pinned messages: {}", pinned.join(", "));
+        }
+        return Ok(CommandResult::Continue);
+    };
+
+    let message_count = history.message_count()?;
+    match number.parse::<usize>() {
+        Ok(n) if (1..=message_count).contains(&n) => {
+            if !front_matter.pinned.contains(&n) {
+                front_matter.pinned.push(n);
+                front_matter.pinned.sort_unstable();
+                history.update_front_matter(front_matter)?;
+            }
+            println!("Pinned message {n}");
+        }
+        _ => eprintln!("Error: Invalid message number: {number}, the chat has {message_count} messages"),
+    }
+
+    Ok(CommandResult::Continue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::setup_test_environment;
+    use std::io;
+
+    #[test]
+    fn test_pin_command() -> io::Result<()> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        history.append_user_input("Second message", None)?;
+
+        let params = CommandParams::new(vec!["2".to_string()], &mut client, &mut history, dir_path.clone());
+        pin_command(params)?;
+        let params = CommandParams::new(vec!["3".to_string()], &mut client, &mut history, dir_path);
+        pin_command(params)?;
+
+        assert_eq!(history.front_matter().unwrap().pinned, vec![2]);
+        assert_eq!(history.pinned_message_indices(), vec![1]);
+        Ok(())
+    }
+}
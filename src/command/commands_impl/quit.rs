@@ -37,6 +37,11 @@ pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String
 }
 
 pub(crate) fn quit_command(command_params: CommandParams) -> io::Result<CommandResult> {
+    if command_params.history.is_temporary() {
+        println!("Ending conversation. The temporary chat was not saved");
+        return Ok(CommandResult::Quit);
+    }
+
     println!(
         "Ending conversation. All interactions saved to '{}'",
         command_params.history.filename
@@ -0,0 +1,76 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::command::commands::{CommandParams, CommandResult, CommandStruct};
+use crate::config::profiles_config::ModelType;
+use std::collections::HashMap;
+use std::io;
+
+pub(crate) fn new<'a>(_default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    (
+        "retry".to_string(),
+        CommandStruct::new(
+            "retry",
+            "Discard the last response and send the last prompt again. Optionally switch to another model type first.",
+            Some(":retry [model_type]"),
+            None,
+            retry_command,
+            None,
+        ),
+    )
+}
+
+pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    new(default_prefixes)
+}
+
+pub(crate) fn retry_command(command_params: CommandParams) -> io::Result<CommandResult> {
+    match command_params.args.first() {
+        Some(model_type) => match ModelType::parse_model_type(model_type) {
+            Ok(model_type) => Ok(CommandResult::Retry(Some(model_type))),
+            Err(_) => {
+                eprintln!(
+                    "Error: Invalid model type specified: {}. Usage: :retry [model_type]",
+                    model_type
+                );
+                eprintln!("Valid models types are 'fast', 'balanced', or 'deep'\n");
+                Ok(CommandResult::Continue)
+            }
+        },
+        None => Ok(CommandResult::Retry(None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::setup_test_environment;
+    use std::io;
+
+    #[test]
+    fn test_retry_command() -> io::Result<()> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        let params = CommandParams::new(vec![], &mut client, &mut history, dir_path.clone());
+        assert!(matches!(retry_command(params)?, CommandResult::Retry(None)));
+
+        let params = CommandParams::new(vec!["deep".to_string()], &mut client, &mut history, dir_path.clone());
+        assert!(matches!(retry_command(params)?, CommandResult::Retry(Some(ModelType::Deep))));
+
+        let params = CommandParams::new(vec!["invalid".to_string()], &mut client, &mut history, dir_path);
+        assert!(matches!(retry_command(params)?, CommandResult::Continue));
+        Ok(())
+    }
+}
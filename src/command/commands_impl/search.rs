@@ -0,0 +1,100 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::command::commands::{CommandParams, CommandResult, CommandStruct};
+use crate::history_file::{search_files, SearchQuery};
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+pub(crate) fn new<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    (
+        "search".to_string(),
+        CommandStruct::new(
+            "search",
+            "Search the messages of all chats in the data directory. Switch to a result with :switch #<number>.",
+            Some(":search <words | \"phrase\" | /regex/> <optional role:user|assistant|tool|summary>"),
+            None,
+            search_command,
+            default_prefixes.get("search").cloned(),
+        ),
+    )
+}
+
+pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    new(default_prefixes)
+}
+
+pub(crate) fn search_command(command_params: CommandParams) -> io::Result<CommandResult> {
+    let query = match SearchQuery::parse(&command_params.args.join(" ")) {
+        Ok(query) => query,
+        Err(e) => {
+            eprintln!("Error: {e}. Usage: :search <words | \"phrase\" | /regex/> <optional role:user|assistant|tool|summary>");
+            return Ok(CommandResult::Continue);
+        }
+    };
+
+    let cforge_dir = Path::new(&command_params.cforge_dir);
+    let hits = search_files(cforge_dir, &query)?;
+    if hits.is_empty() {
+        println!("No messages found");
+        return Ok(CommandResult::Continue);
+    }
+
+    for (i, hit) in hits.iter().enumerate() {
+        println!("{}", hit.display(i + 1, cforge_dir));
+    }
+    println!("Found {} messages, switch to a chat with :switch #<number>", hits.len());
+
+    Ok(CommandResult::SetResults(
+        hits.into_iter().map(|hit| hit.path.display().to_string()).collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::setup_test_environment;
+    use std::{fs, io};
+
+    #[test]
+    fn test_search_command() -> io::Result<()> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        fs::write(format!("{dir_path}/other.md"), "Another conversation")?;
+
+        let args = vec!["conversation".to_string(), "role:user".to_string()];
+        let params = CommandParams::new(args, &mut client, &mut history, dir_path.clone());
+        let result = search_command(params)?;
+
+        match result {
+            CommandResult::SetResults(results) => assert_eq!(
+                results,
+                vec![format!("{dir_path}/other.md"), format!("{dir_path}/test-history.txt")]
+            ),
+            _ => panic!("Expected SetResults"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_command_invalid_query() -> io::Result<()> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        let params = CommandParams::new(vec![], &mut client, &mut history, dir_path);
+        let result = search_command(params)?;
+        assert!(matches!(result, CommandResult::Continue));
+        Ok(())
+    }
+}
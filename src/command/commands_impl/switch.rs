@@ -23,8 +23,8 @@ pub(crate) fn new<'a>(default_prefixes: &HashMap<String, String>) -> (String, Co
         "switch".to_string(),
         CommandStruct::new(
             "switch",
-            "Switch to a different history file. Either relative to the data directory or absolute path. Creates the file if it doesn't exist.",
-            Some(":switch <history file>"),
+            "Switch to a different history file. Either relative to the data directory or absolute path. Creates the file if it doesn't exist. Use #<number> for a result of :search or :list.",
+            Some(":switch <history file | #number>"),
             Some(FileCommandDirectory::Cforge),
             switch_command,
             default_prefixes.get("switch").cloned(),
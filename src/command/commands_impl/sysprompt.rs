@@ -46,7 +46,6 @@ pub(crate) fn sysprompt_command(command_params: CommandParams) -> io::Result<Com
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::api::ChatClient;
     use crate::test_support::setup_test_environment;
     use std::io;
 
@@ -16,13 +16,21 @@
 
 use crate::command::commands::{CommandParams, CommandResult, CommandStruct};
 use crate::tool::tools::get_tools;
+use colored::Colorize;
 use std::collections::HashMap;
 use std::io;
 
 pub(crate) fn new<'a>(_default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
     (
         "tools".to_string(),
-        CommandStruct::new("tools", "display cforge tools", None, None, tools_command, None),
+        CommandStruct::new(
+            "tools",
+            "Display cforge tools, or enable/disable a tool for the current chat",
+            Some(":tools <optional enable|disable> <tool name>"),
+            None,
+            tools_command,
+            None,
+        ),
     )
 }
 
@@ -30,11 +38,95 @@ pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String
     new(default_prefixes)
 }
 
-pub(crate) fn tools_command(_: CommandParams) -> io::Result<CommandResult> {
+pub(crate) fn tools_command(command_params: CommandParams) -> io::Result<CommandResult> {
     let tools = get_tools();
-    for tool in tools {
-        println!("{tool}");
+    let enabled_tools = command_params.chat_client.enabled_tools();
+
+    let Some(action) = command_params.args.first() else {
+        let mut disabled = vec![];
+        for tool in &tools {
+            println!("{tool}");
+            if enabled_tools.as_ref().is_some_and(|enabled| !enabled.contains(&tool.name)) {
+                disabled.push(tool.name.clone());
+            }
+        }
+
+        if !disabled.is_empty() {
+            println!("{} {}", "Disabled tools:".bold().red(), disabled.join(", "));
+        }
+
+        return Ok(CommandResult::Continue);
+    };
+
+    // Tool names may contain whitespace, e.g. `Git Diff`
+    let tool_name = command_params.args[1..].join(" ");
+    let Some(tool) = tools.iter().find(|t| t.name.eq_ignore_ascii_case(&tool_name)) else {
+        eprintln!("Error: Unknown tool '{tool_name}'. Usage: :tools <enable|disable> <tool name>");
+        return Ok(CommandResult::Continue);
+    };
+
+    let mut enabled = enabled_tools.unwrap_or_else(|| tools.iter().map(|t| t.name.clone()).collect());
+    match action.as_str() {
+        "enable" => {
+            if !enabled.contains(&tool.name) {
+                enabled.push(tool.name.clone());
+            }
+            println!("Enabled tool: {}", tool.name);
+        }
+        "disable" => {
+            enabled.retain(|name| name != &tool.name);
+            println!("Disabled tool: {}", tool.name);
+        }
+        _ => {
+            eprintln!("Error: Unknown action '{action}'. Usage: :tools <enable|disable> <tool name>");
+            return Ok(CommandResult::Continue);
+        }
     }
 
+    // Keep the default state when every tool is enabled
+    let all_enabled = tools.iter().all(|t| enabled.contains(&t.name));
+    command_params
+        .chat_client
+        .update_enabled_tools(if all_enabled { None } else { Some(enabled) });
+
     Ok(CommandResult::Continue)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::setup_test_environment;
+    use std::io;
+
+    #[test]
+    fn test_tools_disable_and_enable() -> io::Result<()> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+
+        let args = vec!["disable".to_string(), "Git".to_string(), "Diff".to_string()];
+        let params = CommandParams::new(args, &mut client, &mut history, dir_path.clone());
+        tools_command(params)?;
+
+        let enabled = client.enabled_tools().unwrap();
+        assert!(!enabled.contains(&"Git Diff".to_string()));
+        assert!(enabled.contains(&"grep".to_string()));
+
+        let args = vec!["enable".to_string(), "git".to_string(), "diff".to_string()];
+        let params = CommandParams::new(args, &mut client, &mut history, dir_path);
+        tools_command(params)?;
+
+        assert_eq!(client.enabled_tools(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tools_unknown_tool() -> io::Result<()> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        let args = vec!["disable".to_string(), "nonexistent".to_string()];
+        let params = CommandParams::new(args, &mut client, &mut history, dir_path);
+        let result = tools_command(params)?;
+
+        assert!(matches!(result, CommandResult::Continue));
+        assert_eq!(client.enabled_tools(), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::command::commands::{CommandParams, CommandResult, CommandStruct};
+use std::collections::HashMap;
+use std::io;
+
+pub(crate) fn new<'a>(_default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    (
+        "undo".to_string(),
+        CommandStruct::new(
+            "undo",
+            "Remove the last prompt and its response from the history file",
+            None,
+            None,
+            undo_command,
+            None,
+        ),
+    )
+}
+
+pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    new(default_prefixes)
+}
+
+pub(crate) fn undo_command(command_params: CommandParams) -> io::Result<CommandResult> {
+    match command_params.history.pop_last_exchange()? {
+        Some(_) => println!("Removed the last exchange"),
+        None => println!("Nothing to undo"),
+    }
+
+    Ok(CommandResult::Continue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::setup_test_environment;
+    use std::io;
+
+    #[test]
+    fn test_undo_command() -> io::Result<()> {
+        colored::control::set_override(false);
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        history.append_user_input("Question", None)?;
+        history.append_ai_response("Answer", None)?;
+        assert_eq!(history.message_count()?, 3);
+
+        let params = CommandParams::new(vec![], &mut client, &mut history, dir_path);
+        let result = undo_command(params)?;
+
+        assert!(matches!(result, CommandResult::Continue));
+        assert_eq!(history.message_count()?, 1);
+        assert_eq!(history.get_content(), "Test conversation content");
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::command::commands::{CommandParams, CommandResult, CommandStruct};
+use std::collections::HashMap;
+use std::io;
+
+pub(crate) fn new<'a>(_default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    (
+        "unpin".to_string(),
+        CommandStruct::new(
+            "unpin",
+            "Unpin a pinned message",
+            Some(":unpin <message number>"),
+            None,
+            unpin_command,
+            None,
+        ),
+    )
+}
+
+pub(crate) fn command<'a>(default_prefixes: &HashMap<String, String>) -> (String, CommandStruct<'a>) {
+    new(default_prefixes)
+}
+
+pub(crate) fn unpin_command(command_params: CommandParams) -> io::Result<CommandResult> {
+    let Some(number) = command_params.args.first() else {
+        eprintln!("Error: No message number specified. Usage: :unpin <message number>");
+        return Ok(CommandResult::Continue);
+    };
+
+    let history = command_params.history;
+    let mut front_matter = history.front_matter().cloned().unwrap_or_default();
+
+    match number.parse::<usize>() {
+        Ok(n) if front_matter.pinned.contains(&n) => {
+            front_matter.pinned.retain(|&pinned| pinned != n);
+            history.update_front_matter(front_matter)?;
+            println!("Unpinned message {n}");
+        }
+        _ => eprintln!("Error: Message {number} is not pinned"),
+    }
+
+    Ok(CommandResult::Continue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history_file::FrontMatter;
+    use crate::test_support::setup_test_environment;
+    use std::io;
+
+    #[test]
+    fn test_unpin_command() -> io::Result<()> {
+        let (mut client, mut history, _tmp, dir_path) = setup_test_environment();
+        history.update_front_matter(FrontMatter {
+            pinned: vec![1],
+            ..Default::default()
+        })?;
+
+        let params = CommandParams::new(vec!["1".to_string()], &mut client, &mut history, dir_path);
+        unpin_command(params)?;
+
+        assert!(history.front_matter().unwrap().pinned.is_empty());
+        Ok(())
+    }
+}
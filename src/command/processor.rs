@@ -13,26 +13,45 @@
  * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
  * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
-use crate::api::{ChatClient, ChatResponse};
+use crate::api::client_util::fit_history_to_budget;
+use crate::api::{get_chat_client_implementation, rebuild_chat_client, ChatClient, ChatResponse};
+use crate::command::commands_impl::compact::DEFAULT_KEEP_MESSAGES;
 use crate::command::command_util::get_editor;
 use crate::command::commands::{CommandParams, CommandResult, CommandStruct};
+use crate::config::profiles_config::ModelType;
 use crate::config::AppConfig;
-use crate::history_file::HistoryFile;
+use crate::context::{CommandLimits, ContextChange, ContextSet, LoadedContext};
+use crate::estimate_token_count;
+use crate::history_file::{ChatIndex, FrontMatter, HistoryFile, IndexEntry, MessageMetadata};
+use crate::render;
 use crate::tool::tools::get_tools;
 use crate::user_input::{Command, UserInput};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::{fs, io};
 
+static COMPACT_SYSTEM_PROMPT: &str = "You summarize conversations between a user and an AI assistant. \
+Keep facts, decisions, code and open questions that later messages may rely on. Answer only with the summary.";
+
+static COMPACT_PROMPT: &str = "Summarize the conversation so far.";
+
+static TITLE_SYSTEM_PROMPT: &str = "You name conversations between a user and an AI assistant.";
+
+static TITLE_PROMPT: &str = "Give the conversation so far a short title of at most six words and a one-sentence summary. \
+Answer with exactly two lines:\nTitle: <title>\nSummary: <summary>";
+
+/// Number of messages sent to the model when a chat is titled
+const TITLE_MESSAGES: usize = 4;
+
 pub(crate) struct CommandProcessor<'a> {
     chat_client: &'a mut Box<dyn ChatClient>,
     history: &'a mut HistoryFile,
     app_config: &'a mut AppConfig,
     command_registry: &'a HashMap<String, CommandStruct<'a>>,
-    context_file_path: &'a mut Option<PathBuf>,
+    context: &'a mut ContextSet,
     rebuild_chat_client: &'a mut bool,
-    context_file_content: Option<String>,
+    loaded_context: &'a LoadedContext,
 }
 
 impl<'a> CommandProcessor<'a> {
@@ -41,22 +60,24 @@ impl<'a> CommandProcessor<'a> {
         history: &'a mut HistoryFile,
         app_config: &'a mut AppConfig,
         command_registry: &'a HashMap<String, CommandStruct<'a>>,
-        context_file_path: &'a mut Option<PathBuf>,
+        context: &'a mut ContextSet,
         rebuild_chat_client: &'a mut bool,
-        context_file_content: Option<String>,
+        loaded_context: &'a LoadedContext,
     ) -> Self {
         Self {
             chat_client,
             history,
             app_config,
             command_registry,
-            context_file_path,
+            context,
             rebuild_chat_client,
-            context_file_content,
+            loaded_context,
         }
     }
 
     pub fn process(&mut self, input: &str) -> io::Result<CommandResult> {
+        self.history.reload_if_changed();
+
         match UserInput::parse(input) {
             UserInput::Command(command) => self.handle_command(command),
             UserInput::Prompt(prompt) => self.handle_prompt(prompt),
@@ -76,24 +97,23 @@ impl<'a> CommandProcessor<'a> {
 
             match &result {
                 CommandResult::SwitchHistory(new_file) => {
+                    let new_file = match self.app_config.resolve_history_file(new_file) {
+                        Ok(new_file) => new_file,
+                        Err(e) => {
+                            eprintln!("Error: {e}");
+                            return Ok(CommandResult::Continue);
+                        }
+                    };
                     *self.history = HistoryFile::new(
                         new_file.clone(),
                         self.app_config.data_dir.display().to_string(),
                     )?;
                     self.app_config.update_last_history_file(new_file.clone());
-                    println!("{}", self.history.get_content());
+                    render::print_recent(self.history);
                     println!("Switched to history file: {}", self.history.filename);
+                    self.restore_session_settings();
                 }
-                CommandResult::SwitchContext(new_context) => match new_context {
-                    Some(new_path) => {
-                        *self.context_file_path = Some(new_path.clone());
-                        println!("Updated context file");
-                    }
-                    None => {
-                        *self.context_file_path = None;
-                        println!("Removed context file");
-                    }
-                },
+                CommandResult::UpdateContext(change) => self.update_context(change),
                 CommandResult::HandlePrompt(prompt_file, user_prompt) => match user_prompt {
                     None => {
                         let editor = get_editor();
@@ -149,9 +169,45 @@ impl<'a> CommandProcessor<'a> {
                         println!();
                     }
                 }
+                CommandResult::Retry(model_type) => {
+                    if let Some(model_type) = model_type {
+                        let Some(model) = self.app_config.current_profile.maybe_model(model_type) else {
+                            println!(
+                                "Model of type {} not found in profile {}",
+                                model_type, self.app_config.current_profile.name
+                            );
+                            return Ok(CommandResult::Continue);
+                        };
+                        self.app_config.switch_model(&model);
+                        *self.chat_client = rebuild_chat_client(self.chat_client.as_ref(), self.app_config);
+                    }
+
+                    match self.history.pop_last_exchange()? {
+                        Some(prompt) => {
+                            self.handle_prompt(prompt)?;
+                        }
+                        None => println!("Nothing to retry"),
+                    }
+                }
+                CommandResult::SendPrompt(prompt) => {
+                    self.handle_prompt(prompt.clone())?;
+                }
+                CommandResult::Compact(keep) => self.compact_history(*keep)?,
+                CommandResult::SetResults(results) => {
+                    self.app_config.update_last_results(results.clone())
+                }
+                CommandResult::SwitchTemporary => {
+                    *self.history = HistoryFile::temporary();
+                    println!("Switched to a temporary chat, use :persist <history file> to save it");
+                }
                 _ => {}
             }
 
+            // Chats without front matter get their settings recorded with the first prompt
+            if !matches!(result, CommandResult::Quit) && self.history.front_matter().is_some() {
+                self.sync_session_settings()?;
+            }
+
             Ok(result)
         } else {
             println!("Unknown command: {}", command.name);
@@ -159,6 +215,246 @@ impl<'a> CommandProcessor<'a> {
         }
     }
 
+    /// Apply the session settings stored in the front matter of the current history file
+    pub(crate) fn restore_session_settings(&mut self) {
+        let Some(front_matter) = self.history.front_matter().cloned() else {
+            return;
+        };
+
+        if self.app_config.restore_session(&front_matter) {
+            *self.rebuild_chat_client = true;
+        }
+
+        self.chat_client.update_system_prompt(
+            front_matter
+                .system_prompt
+                .clone()
+                .unwrap_or_else(|| self.app_config.user_config.system_prompt.clone()),
+        );
+        self.chat_client.update_enabled_tools(front_matter.tools.clone());
+        *self.context = ContextSet::from_front_matter(&front_matter);
+    }
+
+    fn update_context(&mut self, change: &ContextChange) {
+        match change {
+            ContextChange::Set(entries) => {
+                *self.context = ContextSet::new(entries.iter().cloned());
+                println!("Updated context files");
+            }
+            ContextChange::Add(entries) => {
+                for entry in entries {
+                    if self.context.add(entry.clone()) {
+                        println!("Added {entry} to the context");
+                    } else {
+                        println!("{entry} is already in the context");
+                    }
+                }
+            }
+            ContextChange::Remove(entries) => {
+                // Numbers refer to the list before the removal
+                let found: Vec<_> = entries
+                    .iter()
+                    .map(|entry| (entry, self.context.find(entry).cloned()))
+                    .collect();
+                for (entry, found) in found {
+                    match found {
+                        Some(found) if self.context.remove(&found.source) => {
+                            println!("Removed {} from the context", found.source)
+                        }
+                        _ => println!("No context entry {entry}"),
+                    }
+                }
+            }
+            ContextChange::SetMode(entry, mode) => match self.context.set_mode(entry, *mode) {
+                Some(changed) => println!("Changed {} to the {mode} mode", changed.source),
+                None => println!("No context entry {entry}"),
+            },
+            ContextChange::List => self
+                .context
+                .print_list(&CommandLimits::from_config(&self.app_config.user_config)),
+            ContextChange::Clear => {
+                self.context.clear();
+                println!("Removed all context files and commands");
+            }
+        }
+    }
+
+    /// Record the current session settings in the front matter of the history file
+    fn sync_session_settings(&mut self) -> io::Result<()> {
+        let mut front_matter = self.history.front_matter().cloned().unwrap_or_default();
+        self.apply_session_settings(&mut front_matter);
+
+        if self.history.front_matter() != Some(&front_matter) {
+            self.history.update_front_matter(front_matter)?;
+        }
+
+        Ok(())
+    }
+
+    fn apply_session_settings(&self, front_matter: &mut FrontMatter) {
+        let system_prompt = self.chat_client.system_prompt();
+
+        front_matter.profile = Some(self.app_config.current_profile.name.clone());
+        front_matter.model_type = Some(self.app_config.current_model.model_type);
+        // The default system prompt is not stored to keep the header short
+        front_matter.system_prompt =
+            (system_prompt != self.app_config.user_config.system_prompt).then_some(system_prompt);
+        self.context.apply_to_front_matter(front_matter);
+        front_matter.tools = self.chat_client.enabled_tools();
+    }
+
+    /// Summarize all but the last `keep` messages with the fast model of the current profile.
+    /// The current model is used if the profile has no fast model.
+    fn compact_history(&mut self, keep: usize) -> io::Result<()> {
+        let summary_client = self.fast_client(COMPACT_SYSTEM_PROMPT);
+
+        let compacted = self.history.compact(keep, |messages| {
+            summary_client
+                .generate_response(messages, COMPACT_PROMPT, None)
+                .map(|response| response.content)
+        })?;
+
+        match (compacted, self.history.archive_path()) {
+            (true, Some(archive)) => println!(
+                "Compacted older messages into a summary, the originals were archived to {}",
+                archive.display()
+            ),
+            (true, None) => println!("Compacted older messages into a summary"),
+            (false, _) => println!("Nothing to compact"),
+        }
+
+        Ok(())
+    }
+
+    /// Client for the fast model of the current profile without tools, the current model is used if the
+    /// profile has no fast model
+    fn fast_client(&self, system_prompt: &str) -> Box<dyn ChatClient> {
+        let model = self
+            .app_config
+            .current_profile
+            .maybe_model(&ModelType::Fast)
+            .unwrap_or_else(|| self.app_config.current_model.clone());
+
+        let mut client = get_chat_client_implementation(
+            &self.app_config.current_profile.provider,
+            &model.model,
+            system_prompt.to_string(),
+            self.app_config.user_config.max_tokens,
+        );
+        client.update_enabled_tools(Some(vec![]));
+        client
+    }
+
+    /// Time, profile, model and token counts of a response of the current model
+    fn response_metadata(&self, response: &ChatResponse) -> MessageMetadata {
+        MessageMetadata {
+            profile: Some(self.app_config.current_profile.name.clone()),
+            model: Some(self.app_config.current_model.model.clone()),
+            input_tokens: response.usage.map(|usage| usage.input_tokens),
+            output_tokens: response.usage.map(|usage| usage.output_tokens),
+            ..MessageMetadata::now()
+        }
+    }
+
+    /// Title a chat that has no title yet with the fast model, if `auto_title` is enabled.
+    /// The title and summary are stored in the front matter and the chat index.
+    fn maybe_auto_title(&mut self) -> io::Result<()> {
+        if !self.app_config.user_config.auto_title
+            || self.history.is_temporary()
+            || self.history.front_matter().is_some_and(|front_matter| front_matter.title.is_some())
+            || self.history.message_count()? < 2
+        {
+            return Ok(());
+        }
+
+        let messages: Vec<Value> = self
+            .history
+            .get_content_json()?
+            .as_array()
+            .map(|messages| messages.iter().take(TITLE_MESSAGES).cloned().collect())
+            .unwrap_or_default();
+
+        let response = match self
+            .fast_client(TITLE_SYSTEM_PROMPT)
+            .generate_response(Value::Array(messages), TITLE_PROMPT, None)
+        {
+            Ok(response) => response,
+            Err(e) => {
+                eprintln!("Error generating a title: {e}");
+                return Ok(());
+            }
+        };
+        let Some(entry) = parse_title_response(&response.content) else {
+            eprintln!("The model did not return a title");
+            return Ok(());
+        };
+
+        let mut front_matter = self.history.front_matter().cloned().unwrap_or_default();
+        front_matter.title = Some(entry.title.clone());
+        front_matter.summary = entry.summary.clone();
+        self.history.update_front_matter(front_matter)?;
+
+        let mut index = ChatIndex::load(&self.app_config.data_dir);
+        index.set(Path::new(&self.history.path), entry.clone());
+        index.save(&self.app_config.data_dir)?;
+
+        println!("Titled the chat: {}", entry.title);
+        Ok(())
+    }
+
+    /// Compact the history before sending a prompt if the estimated token usage exceeds the configured threshold
+    fn maybe_auto_compact(&mut self, prompt: &str) -> io::Result<()> {
+        let (Some(threshold), Some(context_size)) = (
+            self.app_config.user_config.compact_threshold,
+            self.chat_client.model_context_size(),
+        ) else {
+            return Ok(());
+        };
+
+        let estimated_tokens = estimate_token_count(self.history.get_content())
+            + estimate_token_count(self.loaded_context.live_content().as_deref().unwrap_or(""))
+            + estimate_token_count(prompt);
+
+        if estimated_tokens * 100 > context_size * threshold {
+            println!("Estimated token usage exceeds {threshold}% of the context, compacting older messages");
+            self.compact_history(DEFAULT_KEEP_MESSAGES)?;
+        }
+
+        Ok(())
+    }
+
+    /// Leave out the oldest messages that do not fit the model's context window.
+    /// The system prompt, context, prompt and the response tokens are reserved first.
+    fn fit_to_context_window(&self, history_json: Value, prompt: &str) -> io::Result<Value> {
+        let Some(context_size) = self.chat_client.model_context_size() else {
+            return Ok(history_json);
+        };
+
+        let reserved_tokens = self.app_config.user_config.max_tokens
+            + estimate_token_count(&self.chat_client.system_prompt())
+            + estimate_token_count(self.loaded_context.live_content().as_deref().unwrap_or(""))
+            + estimate_token_count(prompt);
+        let budget = context_size.saturating_sub(reserved_tokens);
+
+        let (fitted, fit) = fit_history_to_budget(
+            &history_json,
+            &self.history.pinned_message_indices(),
+            budget,
+        );
+
+        if fit.dropped > 0 || fit.truncated > 0 {
+            println!(
+                "Left out {} of the oldest messages and shortened {} to fit the model's context window",
+                fit.dropped, fit.truncated
+            );
+        }
+        if fit.over_budget {
+            println!("Pinned messages do not fit the model's context window");
+        }
+
+        Ok(fitted)
+    }
+
     fn combine(prompt_file: &PathBuf, user_prompt: &str) -> String {
         let prompt_content = fs::read_to_string(prompt_file).unwrap_or_else(|_| String::new());
 
@@ -211,10 +507,13 @@ impl<'a> CommandProcessor<'a> {
                 },
             ]);
             let tool_response = self.chat_client.generate_tool_response(param)?;
+            let metadata = self.response_metadata(&tool_response);
 
+            self.history
+                .append_ai_response(&tool_response.content, Some(&metadata))?;
             println!(
                 "{}",
-                self.history.append_ai_response(&tool_response.content)?
+                render::render_message("assistant", &tool_response.content, Some(&metadata))
             );
 
             self.handle_tools(tool_response, current_tool_calls + 1)
@@ -224,6 +523,15 @@ impl<'a> CommandProcessor<'a> {
     }
 
     fn handle_prompt(&mut self, prompt: String) -> io::Result<CommandResult> {
+        // Context in the once and diff modes is recorded in the history as a part of the prompt
+        let prompt = match self.loaded_context.recorded_content() {
+            Some(recorded) => format!("{prompt}\n\nAdditional context:\n{recorded}"),
+            None => prompt,
+        };
+        self.context.mark_sent(self.loaded_context);
+        self.sync_session_settings()?;
+        self.maybe_auto_compact(&prompt)?;
+
         let history_json = match self.history.get_content_json() {
             Ok(s) => s,
             Err(e) => {
@@ -232,23 +540,78 @@ impl<'a> CommandProcessor<'a> {
             }
         };
 
+        let history_json = self.fit_to_context_window(history_json, &prompt)?;
+
+        let prompt_metadata = MessageMetadata::now();
         let llm_response = self.chat_client.generate_response(
             history_json,
             &prompt,
-            self.context_file_content.as_deref(),
+            self.loaded_context.live_content().as_deref(),
         )?;
+        let response_metadata = self.response_metadata(&llm_response);
 
-        self.history.append_user_input(&prompt)?;
+        self.history.append_user_input(&prompt, Some(&prompt_metadata))?;
 
-        // Print and save the initial AI response with the delimiter
-        println!(
-            "{}",
-            self.history
-                .maybe_append_ai_response(&llm_response.content)?
-        );
+        // Save and print the initial AI response
+        if self
+            .history
+            .maybe_append_ai_response(&llm_response.content, Some(&response_metadata))?
+        {
+            println!(
+                "{}",
+                render::render_message("assistant", &llm_response.content, Some(&response_metadata))
+            );
+        }
 
         self.handle_tools(llm_response, 0)?;
+        self.maybe_auto_title()?;
 
         Ok(CommandResult::Continue)
     }
 }
+
+/// Read the title and optional summary from the model's answer to `TITLE_PROMPT`.
+/// Without a `Title:` line, the first line is used as the title.
+fn parse_title_response(response: &str) -> Option<IndexEntry> {
+    let clean = |text: &str| text.trim().trim_matches(['"', '*', '#']).trim().to_string();
+    let field = |name: &str| {
+        response.lines().find_map(|line| {
+            let line = clean(line);
+            let (key, value) = line.split_once(':')?;
+            key.trim().eq_ignore_ascii_case(name).then(|| clean(value))
+        })
+    };
+
+    let title = field("title")
+        .or_else(|| response.lines().map(clean).find(|line| !line.is_empty()))
+        .filter(|title| !title.is_empty())?;
+
+    Some(IndexEntry {
+        title,
+        summary: field("summary").filter(|summary| !summary.is_empty()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_title_response() {
+        assert_eq!(
+            parse_title_response("Title: \"Rust lifetimes\"\nSummary: How lifetimes relate to borrows."),
+            Some(IndexEntry {
+                title: "Rust lifetimes".to_string(),
+                summary: Some("How lifetimes relate to borrows.".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_title_response("**Rust lifetimes**\n"),
+            Some(IndexEntry {
+                title: "Rust lifetimes".to_string(),
+                summary: None,
+            })
+        );
+        assert_eq!(parse_title_response("  \n"), None);
+    }
+}
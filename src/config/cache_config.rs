@@ -30,6 +30,8 @@ pub struct CacheConfig {
     pub last_history_file: Option<String>,
     pub last_profile_name: Option<String>,
     pub profile_models: Option<HashMap<String, ModelType>>,
+    /// History files of the last `:search` or `:list`, referred to as `#1`, `#2`, ...
+    pub last_results: Option<Vec<String>>,
 }
 
 impl Default for CacheConfig {
@@ -48,6 +50,7 @@ impl CacheConfig {
             last_history_file,
             last_profile_name,
             profile_models,
+            last_results: None,
         }
     }
 
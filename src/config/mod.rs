@@ -20,6 +20,7 @@ use rustyline::{history::DefaultHistory, Cmd, Config, Editor, EventHandler, KeyE
 use crate::command::command_complete::CommandHelper;
 use crate::command::commands::{CommandStruct, FileCommandDirectory};
 use crate::config::profiles_config::{Model, ModelType, Profile};
+use crate::history_file::FrontMatter;
 pub(crate) use crate::config::{cache_config::CacheConfig, rustyline_config::build, user_config::UserConfig};
 
 pub mod cache_config;
@@ -120,6 +121,26 @@ impl AppConfig {
         self.cache_config.save(get_cache_path());
     }
 
+    pub(crate) fn update_last_results(&mut self, results: Vec<String>) {
+        self.cache_config.last_results = Some(results);
+        self.cache_config.save(get_cache_path());
+    }
+
+    /// Resolve a `#<number>` reference to a result of the last `:search` or `:list`,
+    /// other history file paths are returned as is
+    pub(crate) fn resolve_history_file(&self, history_file: &str) -> Result<String, String> {
+        let Some(number) = history_file.strip_prefix('#') else {
+            return Ok(history_file.to_string());
+        };
+
+        number
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| number.checked_sub(1))
+            .and_then(|index| self.cache_config.last_results.as_ref()?.get(index).cloned())
+            .ok_or_else(|| format!("No result {history_file}, run :search or :list first"))
+    }
+
     pub fn get_profile(&mut self) -> Profile {
         if let Some(last_profile_name) = self.cache_config.last_profile_name.clone() {
             return self.user_config.find_profile(&last_profile_name).clone();
@@ -176,6 +197,42 @@ impl AppConfig {
 
         println!("Switched to model: {}", model.model);
     }
+
+    /// Switch to the profile and model type stored in a history file's front matter.
+    ///
+    /// Returns `true` if the active profile or model changed.
+    pub(crate) fn restore_session(&mut self, front_matter: &FrontMatter) -> bool {
+        let mut changed = false;
+
+        if let Some(profile_name) = &front_matter.profile
+            && profile_name != &self.current_profile.name
+        {
+            match self.maybe_profile(profile_name) {
+                Some(profile) => {
+                    self.switch_profile(&profile);
+                    changed = true;
+                }
+                None => eprintln!("Profile '{profile_name}' from the history file was not found"),
+            }
+        }
+
+        if let Some(model_type) = &front_matter.model_type
+            && model_type != &self.current_model.model_type
+        {
+            match self.current_profile.maybe_model(model_type) {
+                Some(model) => {
+                    self.switch_model(&model);
+                    changed = true;
+                }
+                None => eprintln!(
+                    "Model of type {model_type} from the history file not found in profile {}",
+                    self.current_profile.name
+                ),
+            }
+        }
+
+        changed
+    }
 }
 
 impl Default for AppConfig {
@@ -286,7 +343,20 @@ mod tests {
     use crate::command::commands::{
         CommandParams, CommandResult, CommandStruct, FileCommandDirectory,
     };
-    use crate::config::get_commands;
+    use crate::config::{get_commands, AppConfig};
+
+    #[test]
+    fn resolve_history_file() {
+        let mut app_config = AppConfig::default();
+        assert_eq!(app_config.resolve_history_file("chat.md"), Ok("chat.md".to_string()));
+        assert!(app_config.resolve_history_file("#1").is_err());
+
+        app_config.cache_config.last_results = Some(vec!["/data/a.md".to_string(), "/data/b.md".to_string()]);
+        assert_eq!(app_config.resolve_history_file("#2"), Ok("/data/b.md".to_string()));
+        assert!(app_config.resolve_history_file("#0").is_err());
+        assert!(app_config.resolve_history_file("#3").is_err());
+        assert!(app_config.resolve_history_file("#x").is_err());
+    }
 
     #[test]
     fn get_commands_base() {
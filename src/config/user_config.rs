@@ -35,6 +35,26 @@ pub struct UserConfig {
     #[serde(default = "default_max_tokens")]
     pub max_tokens: usize,
 
+    /// Compact the chat automatically when the estimated token usage exceeds this percentage of the context
+    #[serde(default)]
+    pub compact_threshold: Option<usize>,
+
+    /// Name new chats with the fast model of the current profile after the first exchange
+    #[serde(default)]
+    pub auto_title: bool,
+
+    /// Render assistant responses as markdown in the terminal, the history file keeps the raw markdown
+    #[serde(default = "default_render_markdown")]
+    pub render_markdown: bool,
+
+    /// Seconds a context command may run before it is killed
+    #[serde(default = "default_context_command_timeout")]
+    pub context_command_timeout: u64,
+
+    /// Bytes of output kept from a context command, the end of longer output is kept
+    #[serde(default = "default_context_command_max_output")]
+    pub context_command_max_output: usize,
+
     #[serde(default = "default_command_prefixes")]
     pub command_prefixes: HashMap<String, String>,
 
@@ -117,6 +137,11 @@ impl Default for UserConfig {
             rustyline: RustylineConfig::default(),
             token_estimation: default_token_estimation(),
             max_tokens: default_max_tokens(),
+            compact_threshold: None,
+            auto_title: false,
+            render_markdown: default_render_markdown(),
+            context_command_timeout: default_context_command_timeout(),
+            context_command_max_output: default_context_command_max_output(),
             command_prefixes: default_command_prefixes(),
             profiles_config: ProfilesConfig::default(),
         }
@@ -138,6 +163,18 @@ fn default_token_estimation() -> bool {
     true
 }
 
+fn default_render_markdown() -> bool {
+    true
+}
+
+fn default_context_command_timeout() -> u64 {
+    30
+}
+
+fn default_context_command_max_output() -> usize {
+    20_000
+}
+
 fn default_max_tokens() -> usize {
     1024
 }
@@ -166,8 +203,13 @@ mod tests {
     #[test]
     fn default_values() {
         let config = UserConfig::default();
-        assert_eq!(true, config.token_estimation);
+        assert!(config.token_estimation);
         assert_eq!(1024, config.max_tokens);
+        assert_eq!(None, config.compact_threshold);
+        assert!(!config.auto_title);
+        assert!(config.render_markdown);
+        assert_eq!(30, config.context_command_timeout);
+        assert_eq!(20_000, config.context_command_max_output);
         assert_eq!("", config.knowledge_dir);
 
         assert_eq!(
@@ -212,12 +254,20 @@ mod tests {
         let temp_dir = create_config(
             "
             token_estimation = false
+            compact_threshold = 80
+            auto_title = true
+            render_markdown = false
+            context_command_timeout = 5
             provider = \"anthropic\"
             ",
         );
         let config = UserConfig::load(temp_dir.path().to_path_buf());
 
-        assert_eq!(false, config.token_estimation);
+        assert!(!config.token_estimation);
+        assert_eq!(Some(80), config.compact_threshold);
+        assert!(config.auto_title);
+        assert!(!config.render_markdown);
+        assert_eq!(5, config.context_command_timeout);
     }
 
     #[test]
@@ -226,7 +276,7 @@ mod tests {
         let config = UserConfig::load(temp_dir.path().to_path_buf());
 
         // Should use defaults
-        assert_eq!(true, config.token_estimation);
+        assert!(config.token_estimation);
         assert_eq!(1024, config.max_tokens);
         assert_eq!("", config.knowledge_dir);
     }
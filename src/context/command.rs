@@ -0,0 +1,268 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Shell commands whose output is sent as context
+
+use crate::config::user_config::UserConfig;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Read};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often a running command is checked for completion
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Time to wait for the rest of the output after the command has exited.
+/// Background processes started by the command may keep the output open.
+const OUTPUT_GRACE: Duration = Duration::from_millis(200);
+
+pub(crate) struct CommandLimits {
+    pub(crate) timeout: Duration,
+    /// Bytes of output that are kept, the end of the output is kept
+    pub(crate) max_output: usize,
+}
+
+impl CommandLimits {
+    pub(crate) fn from_config(user_config: &UserConfig) -> Self {
+        CommandLimits {
+            timeout: Duration::from_secs(user_config.context_command_timeout),
+            max_output: user_config.context_command_max_output,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum CommandStatus {
+    Exited(i32),
+    /// Terminated by a signal
+    Terminated,
+    TimedOut(Duration),
+}
+
+impl CommandStatus {
+    pub(crate) fn success(&self) -> bool {
+        *self == CommandStatus::Exited(0)
+    }
+
+    fn from_exit_status(status: ExitStatus) -> Self {
+        status
+            .code()
+            .map_or(CommandStatus::Terminated, CommandStatus::Exited)
+    }
+}
+
+impl Display for CommandStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandStatus::Exited(code) => write!(f, "exit status {code}"),
+            CommandStatus::Terminated => write!(f, "terminated by a signal"),
+            CommandStatus::TimedOut(timeout) => {
+                write!(f, "timed out after {}s", timeout.as_secs_f32())
+            }
+        }
+    }
+}
+
+pub(crate) struct CommandOutput {
+    /// Standard output and error interleaved as they were written
+    pub(crate) output: String,
+    pub(crate) status: CommandStatus,
+}
+
+/// Run a command with the shell, capturing its output. The command is killed after the timeout.
+pub(crate) fn run(command: &str, limits: &CommandLimits) -> io::Result<CommandOutput> {
+    let (mut reader, writer) = io::pipe()?;
+
+    let mut shell = shell(command);
+    shell
+        .stdin(Stdio::null())
+        .stdout(writer.try_clone()?)
+        .stderr(writer);
+    let mut child = shell.spawn()?;
+    // The command holds the write end of the pipe, the output ends only once it is dropped
+    drop(shell);
+
+    let output = Arc::new(Mutex::new(TailBuffer::new(limits.max_output)));
+    let (done_sender, done_receiver) = mpsc::channel();
+    let reader_output = Arc::clone(&output);
+    thread::spawn(move || {
+        let mut chunk = [0; 8192];
+        loop {
+            match reader.read(&mut chunk) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => reader_output.lock().unwrap().push(&chunk[..read]),
+            }
+        }
+        let _ = done_sender.send(());
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break CommandStatus::from_exit_status(status);
+        }
+        if start.elapsed() >= limits.timeout {
+            kill(&mut child);
+            break CommandStatus::TimedOut(limits.timeout);
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    let _ = done_receiver.recv_timeout(OUTPUT_GRACE);
+
+    let output = output.lock().unwrap().to_string();
+    Ok(CommandOutput { output, status })
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    use std::os::unix::process::CommandExt;
+
+    let mut shell = Command::new("sh");
+    // A process group of its own, so that the processes started by the command can be killed too
+    shell.arg("-c").arg(command).process_group(0);
+    shell
+}
+
+#[cfg(not(unix))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+fn kill(child: &mut Child) {
+    #[cfg(unix)]
+    let _ = Command::new("kill")
+        .arg("-KILL")
+        .arg(format!("-{}", child.id()))
+        .stderr(Stdio::null())
+        .status();
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Keeps the last `max` bytes written to it
+struct TailBuffer {
+    bytes: Vec<u8>,
+    max: usize,
+    total: usize,
+}
+
+impl TailBuffer {
+    fn new(max: usize) -> Self {
+        TailBuffer {
+            bytes: Vec::new(),
+            max,
+            total: 0,
+        }
+    }
+
+    fn push(&mut self, chunk: &[u8]) {
+        self.total += chunk.len();
+        self.bytes.extend_from_slice(chunk);
+        // Trimmed in batches to avoid moving the buffer on every chunk
+        if self.bytes.len() > self.max * 2 {
+            self.bytes.drain(..self.bytes.len() - self.max);
+        }
+    }
+}
+
+impl Display for TailBuffer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let start = self.bytes.len().saturating_sub(self.max);
+        let mut tail = &self.bytes[start..];
+        if self.total > tail.len() {
+            // Do not start in the middle of a multibyte character
+            while tail
+                .first()
+                .is_some_and(|byte| byte & 0b1100_0000 == 0b1000_0000)
+            {
+                tail = &tail[1..];
+            }
+            writeln!(f, "[{} earlier bytes left out]", self.total - tail.len())?;
+        }
+        write!(f, "{}", String::from_utf8_lossy(tail))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn limits(timeout: Duration, max_output: usize) -> CommandLimits {
+        CommandLimits {
+            timeout,
+            max_output,
+        }
+    }
+
+    #[test]
+    fn test_run_captures_output_and_status() {
+        let result = run(
+            "echo out; echo err >&2; exit 3",
+            &limits(Duration::from_secs(10), 1000),
+        )
+        .unwrap();
+
+        assert_eq!(result.output, "out\nerr\n");
+        assert_eq!(result.status, CommandStatus::Exited(3));
+        assert_eq!(result.status.to_string(), "exit status 3");
+        assert!(!result.status.success());
+    }
+
+    #[test]
+    fn test_run_timeout() {
+        let start = Instant::now();
+        let result = run(
+            "echo started; sleep 10",
+            &limits(Duration::from_millis(200), 1000),
+        )
+        .unwrap();
+
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(result.output, "started\n");
+        assert_eq!(result.status.to_string(), "timed out after 0.2s");
+    }
+
+    #[test]
+    fn test_run_keeps_end_of_output() {
+        let result = run(
+            "for i in $(seq 1 1000); do echo line $i; done",
+            &limits(Duration::from_secs(10), 20),
+        )
+        .unwrap();
+
+        assert!(result.status.success());
+        assert_eq!(
+            result.output,
+            "[8873 earlier bytes left out]\n\nline 999\nline 1000\n"
+        );
+    }
+
+    #[test]
+    fn test_tail_buffer_character_boundary() {
+        let mut buffer = TailBuffer::new(4);
+        buffer.push("aää".as_bytes());
+        assert_eq!(buffer.to_string(), "[1 earlier bytes left out]\nää");
+
+        let mut buffer = TailBuffer::new(4);
+        buffer.push("ääa".as_bytes());
+        assert_eq!(buffer.to_string(), "[2 earlier bytes left out]\näa");
+    }
+}
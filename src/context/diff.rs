@@ -0,0 +1,207 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Line based unified diffs of context snapshots
+
+/// Unchanged lines shown around each change
+const CONTEXT_LINES: usize = 3;
+
+/// Larger differences are not diffed, the full content is sent instead
+const MAX_EDIT_DISTANCE: usize = 2000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Edit {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Unified diff of the lines of `old` and `new` without the file headers.
+/// Returns `None` if the contents differ too much to be diffed.
+pub(crate) fn unified_diff(old: &str, new: &str) -> Option<String> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let edits = edit_script(&old, &new)?;
+
+    let changes: Vec<usize> = (0..edits.len())
+        .filter(|&index| edits[index].0 != Edit::Equal)
+        .collect();
+
+    let mut diff = String::new();
+    let mut index = 0;
+    while index < changes.len() {
+        // Changes closer than twice the context lines share a hunk
+        let mut last = index;
+        while last + 1 < changes.len() && changes[last + 1] - changes[last] <= CONTEXT_LINES * 2 + 1
+        {
+            last += 1;
+        }
+
+        let start = changes[index].saturating_sub(CONTEXT_LINES);
+        let end = (changes[last] + CONTEXT_LINES + 1).min(edits.len());
+        diff.push_str(&render_hunk(&edits, start, end));
+        index = last + 1;
+    }
+
+    Some(diff)
+}
+
+/// Edits with the line they refer to, in the order of the lines
+type EditScript<'a> = Vec<(Edit, &'a str, usize, usize)>;
+
+fn render_hunk(edits: &EditScript, start: usize, end: usize) -> String {
+    let hunk = &edits[start..end];
+    let old_count = hunk.iter().filter(|edit| edit.0 != Edit::Insert).count();
+    let new_count = hunk.iter().filter(|edit| edit.0 != Edit::Delete).count();
+    // An empty range starts at the line before it
+    let (_, _, old_line, new_line) = hunk[0];
+    let old_start = if old_count == 0 {
+        old_line
+    } else {
+        old_line + 1
+    };
+    let new_start = if new_count == 0 {
+        new_line
+    } else {
+        new_line + 1
+    };
+
+    let mut rendered = format!("@@ -{old_start},{old_count} +{new_start},{new_count} @@\n");
+    for (edit, line, _, _) in hunk {
+        let marker = match edit {
+            Edit::Equal => ' ',
+            Edit::Delete => '-',
+            Edit::Insert => '+',
+        };
+        rendered.push(marker);
+        rendered.push_str(line);
+        rendered.push('\n');
+    }
+    rendered
+}
+
+/// Shortest edit script with the Myers algorithm. Each edit has the number of old and new
+/// lines before it. Returns `None` if more than `MAX_EDIT_DISTANCE` edits are needed.
+fn edit_script<'a>(old: &[&'a str], new: &[&'a str]) -> Option<EditScript<'a>> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    // The furthest points of each round, only the diagonals -d - 1..=d + 1 are kept
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max.min(MAX_EDIT_DISTANCE) as isize {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+
+        for k in (-d..=d).step_by(2) {
+            let index = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+                v[index + 1]
+            } else {
+                v[index - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index] = x;
+
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+
+        if d as usize == max.min(MAX_EDIT_DISTANCE) {
+            return None;
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let at = |k: isize| v[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            edits.push((Edit::Equal, old[x as usize], x as usize, y as usize));
+        }
+
+        if d > 0 {
+            if x == prev_x {
+                y -= 1;
+                edits.push((Edit::Insert, new[y as usize], x as usize, y as usize));
+            } else {
+                x -= 1;
+                edits.push((Edit::Delete, old[x as usize], x as usize, y as usize));
+            }
+        }
+    }
+
+    edits.reverse();
+    Some(edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn\n";
+
+        assert_eq!(
+            unified_diff(old, new).unwrap(),
+            "@@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n@@ -11,3 +11,4 @@\n k\n l\n m\n+n\n"
+        );
+        assert_eq!(unified_diff(old, old).unwrap(), "");
+    }
+
+    #[test]
+    fn test_unified_diff_merges_close_changes() {
+        assert_eq!(
+            unified_diff("a\nb\nc\nd\n", "b\nc\nx\n").unwrap(),
+            "@@ -1,4 +1,3 @@\n-a\n b\n c\n-d\n+x\n"
+        );
+        assert_eq!(
+            unified_diff("", "new\n").unwrap(),
+            "@@ -0,0 +1,1 @@\n+new\n"
+        );
+        assert_eq!(
+            unified_diff("old\n", "").unwrap(),
+            "@@ -1,1 +0,0 @@\n-old\n"
+        );
+    }
+
+    #[test]
+    fn test_large_difference_is_not_diffed() {
+        let old: String = (0..3000).map(|i| format!("{i}\n")).collect();
+        let new: String = (0..3000).map(|i| format!("new {i}\n")).collect();
+
+        assert!(unified_diff(&old, &new).is_none());
+    }
+}
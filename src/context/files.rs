@@ -0,0 +1,407 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Expansion of context entries into files. Directories and globs are walked recursively,
+//! skipping the `.git` directory and anything excluded by `.gitignore` files.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the ignore files that are read while walking directories
+const IGNORE_FILE: &str = ".gitignore";
+
+/// Whether the entry contains glob characters and has to be matched against the walked files
+pub(crate) fn is_glob(entry: &str) -> bool {
+    entry.contains(['*', '?', '['])
+}
+
+/// Expand a single context entry into the files it refers to, in a stable order.
+/// Globs and directories are expanded, other paths are returned as they are.
+pub(crate) fn expand(entry: &Path) -> Vec<PathBuf> {
+    let entry_str = entry.to_string_lossy();
+
+    if is_glob(&entry_str) {
+        expand_glob(&entry_str)
+    } else if entry.is_dir() {
+        let mut files = Vec::new();
+        walk(entry, &ancestor_ignores(entry), None, &mut files);
+        files
+    } else {
+        vec![entry.to_path_buf()]
+    }
+}
+
+fn expand_glob(pattern: &str) -> Vec<PathBuf> {
+    let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+    let segments: Vec<&str> = pattern.split('/').collect();
+    let literal = segments
+        .iter()
+        .take_while(|segment| !is_glob(segment))
+        .count();
+
+    let base = segments[..literal].join("/");
+    let base_dir = match base.as_str() {
+        "" if literal == 0 => PathBuf::from("."),
+        "" => PathBuf::from("/"),
+        _ => PathBuf::from(&base),
+    };
+
+    // Without `**` the walk does not have to go deeper than the pattern
+    let rest = &segments[literal..];
+    let max_depth = (!rest.contains(&"**")).then_some(rest.len());
+
+    let mut files = Vec::new();
+    walk(
+        &base_dir,
+        &ancestor_ignores(&base_dir),
+        max_depth,
+        &mut files,
+    );
+    files.retain(|file| {
+        let relative = file.strip_prefix(&base_dir).unwrap_or(file);
+        let candidate = if literal == 0 {
+            to_slash(relative)
+        } else {
+            format!("{base}/{}", to_slash(relative))
+        };
+        glob_match(pattern, &candidate)
+    });
+    files
+}
+
+/// Collect the files below `dir`, reading the ignore file of each directory on the way
+fn walk(dir: &Path, ignores: &[IgnoreFile], max_depth: Option<usize>, files: &mut Vec<PathBuf>) {
+    if max_depth == Some(0) {
+        return;
+    }
+
+    let mut ignores = ignores.to_vec();
+    if let Some(ignore_file) = IgnoreFile::read(dir) {
+        ignores.push(ignore_file);
+    }
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+
+    for path in paths {
+        let is_dir = path.is_dir();
+        if path.file_name().is_some_and(|name| name == ".git")
+            || is_ignored(&ignores, &path, is_dir)
+        {
+            continue;
+        }
+
+        if is_dir {
+            walk(&path, &ignores, max_depth.map(|depth| depth - 1), files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+/// Ignore files of the parent directories up to the root of the git repository.
+/// Nothing is returned if the directory is not inside a repository.
+fn ancestor_ignores(dir: &Path) -> Vec<IgnoreFile> {
+    let Ok(dir) = fs::canonicalize(dir) else {
+        return Vec::new();
+    };
+
+    let mut ignores = Vec::new();
+    let mut current = dir.as_path();
+    loop {
+        if current.join(".git").exists() {
+            break;
+        }
+        match current.parent() {
+            Some(parent) => {
+                current = parent;
+                ignores.extend(IgnoreFile::read(current));
+            }
+            None => return Vec::new(),
+        }
+    }
+
+    // The outermost ignore file is applied first
+    ignores.reverse();
+    ignores
+}
+
+/// The last matching rule decides, so that inner ignore files and negations take precedence
+fn is_ignored(ignores: &[IgnoreFile], path: &Path, is_dir: bool) -> bool {
+    let canonical = fs::canonicalize(path).ok();
+    let mut ignored = false;
+
+    for ignore_file in ignores {
+        let relative = path
+            .strip_prefix(&ignore_file.dir)
+            .ok()
+            .or_else(|| canonical.as_deref()?.strip_prefix(&ignore_file.dir).ok());
+        let Some(relative) = relative else {
+            continue;
+        };
+        let relative = to_slash(relative);
+
+        for rule in &ignore_file.rules {
+            if rule.matches(&relative, is_dir) {
+                ignored = !rule.negated;
+            }
+        }
+    }
+
+    ignored
+}
+
+fn to_slash(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[derive(Clone, Debug)]
+struct IgnoreFile {
+    dir: PathBuf,
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreFile {
+    fn read(dir: &Path) -> Option<Self> {
+        let content = fs::read_to_string(dir.join(IGNORE_FILE)).ok()?;
+        Some(IgnoreFile {
+            dir: dir.to_path_buf(),
+            rules: content.lines().filter_map(IgnoreRule::parse).collect(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct IgnoreRule {
+    pattern: String,
+    negated: bool,
+    dir_only: bool,
+    /// Patterns with a slash are matched against the path relative to the ignore file,
+    /// other patterns against the file name at any depth
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let pattern = line.strip_prefix('/').unwrap_or(line);
+
+        (!pattern.is_empty()).then(|| IgnoreRule {
+            pattern: pattern.to_string(),
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    fn matches(&self, relative: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+
+        if self.anchored {
+            glob_match(&self.pattern, relative)
+        } else {
+            let name = relative.rsplit('/').next().unwrap_or(relative);
+            glob_match(&self.pattern, name)
+        }
+    }
+}
+
+/// Match a slash separated path against a glob. `*`, `?` and `[...]` do not match a slash,
+/// `**` as a whole segment matches any number of directories.
+pub(crate) fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    match_segments(&pattern, &path)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => {
+            match_segments(rest, path) || (!path.is_empty() && match_segments(pattern, &path[1..]))
+        }
+        Some((segment, rest)) => {
+            !path.is_empty()
+                && match_segment(
+                    &segment.chars().collect::<Vec<_>>(),
+                    &path[0].chars().collect::<Vec<_>>(),
+                )
+                && match_segments(rest, &path[1..])
+        }
+    }
+}
+
+fn match_segment(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') => (0..=text.len()).any(|skip| match_segment(&pattern[1..], &text[skip..])),
+        Some('?') => !text.is_empty() && match_segment(&pattern[1..], &text[1..]),
+        Some('[') => match (text.first(), match_class(&pattern[1..])) {
+            (Some(&c), Some((matches, len))) => {
+                matches(c) && match_segment(&pattern[1 + len..], &text[1..])
+            }
+            // An unclosed bracket is matched literally
+            (Some(&c), None) => c == '[' && match_segment(&pattern[1..], &text[1..]),
+            (None, _) => false,
+        },
+        Some('\\') if pattern.len() > 1 => {
+            text.first() == Some(&pattern[1]) && match_segment(&pattern[2..], &text[1..])
+        }
+        Some(&c) => text.first() == Some(&c) && match_segment(&pattern[1..], &text[1..]),
+    }
+}
+
+/// Parse a character class after the opening bracket.
+/// Returns the matcher and the number of characters consumed, including the closing bracket.
+fn match_class(pattern: &[char]) -> Option<(impl Fn(char) -> bool, usize)> {
+    let (negated, start) = match pattern.first() {
+        Some('!' | '^') => (true, 1),
+        _ => (false, 0),
+    };
+    // A closing bracket right after the opening one is part of the class
+    let end = start + 1 + pattern.get(start + 1..)?.iter().position(|&c| c == ']')?;
+    let class: Vec<char> = pattern[start..end].to_vec();
+
+    let matcher = move |c: char| {
+        let mut matched = false;
+        let mut i = 0;
+        while i < class.len() {
+            if i + 2 < class.len() && class[i + 1] == '-' {
+                matched |= class[i] <= c && c <= class[i + 2];
+                i += 3;
+            } else {
+                matched |= class[i] == c;
+                i += 1;
+            }
+        }
+        matched != negated
+    };
+
+    Some((matcher, end + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.rs", "main.rs"));
+        assert!(!glob_match("*.rs", "src/main.rs"));
+        assert!(glob_match("src/**/*.rs", "src/main.rs"));
+        assert!(glob_match(
+            "src/**/*.rs",
+            "src/command/commands_impl/edit.rs"
+        ));
+        assert!(glob_match("**/test?.md", "docs/notes/test1.md"));
+        assert!(glob_match("file[0-9].txt", "file7.txt"));
+        assert!(!glob_match("file[!0-9].txt", "file7.txt"));
+        assert!(glob_match("[]]x", "]x"));
+        assert!(!glob_match("src/*", "src/a/b"));
+    }
+
+    #[test]
+    fn test_ignore_rules() {
+        let rule = IgnoreRule::parse("target/").unwrap();
+        assert!(rule.matches("target", true));
+        assert!(rule.matches("nested/target", true));
+        assert!(!rule.matches("target", false));
+
+        let rule = IgnoreRule::parse("/docs/*.html").unwrap();
+        assert!(rule.anchored);
+        assert!(rule.matches("docs/index.html", false));
+        assert!(!rule.matches("src/docs/index.html", false));
+
+        assert_eq!(IgnoreRule::parse("# comment"), None);
+        assert!(IgnoreRule::parse("!keep.log").unwrap().negated);
+    }
+
+    #[test]
+    fn test_expand_respects_gitignore() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n*.log\n!keep.log\n").unwrap();
+        fs::write(root.join("src/.gitignore"), "generated.rs\n").unwrap();
+        fs::write(root.join(".git/config"), "").unwrap();
+        fs::write(root.join("target/out.rs"), "").unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join("src/generated.rs"), "").unwrap();
+        fs::write(root.join("src/nested/lib.rs"), "").unwrap();
+        fs::write(root.join("src/debug.log"), "").unwrap();
+        fs::write(root.join("src/keep.log"), "").unwrap();
+
+        let relative = |files: Vec<PathBuf>| -> Vec<String> {
+            files
+                .iter()
+                .map(|file| to_slash(file.strip_prefix(root).unwrap()))
+                .collect()
+        };
+
+        assert_eq!(
+            relative(expand(root)),
+            vec![
+                ".gitignore",
+                "src/.gitignore",
+                "src/keep.log",
+                "src/main.rs",
+                "src/nested/lib.rs"
+            ]
+        );
+        // The ignore files of the parent directories apply when a subdirectory is added
+        assert_eq!(
+            relative(expand(&root.join("src"))),
+            vec![
+                "src/.gitignore",
+                "src/keep.log",
+                "src/main.rs",
+                "src/nested/lib.rs"
+            ]
+        );
+
+        let pattern = format!("{}/**/*.rs", root.display());
+        assert_eq!(
+            relative(expand(Path::new(&pattern))),
+            vec!["src/main.rs", "src/nested/lib.rs"]
+        );
+        let pattern = format!("{}/src/*.rs", root.display());
+        assert_eq!(relative(expand(Path::new(&pattern))), vec!["src/main.rs"]);
+    }
+}
@@ -0,0 +1,714 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Files and command output that are sent as additional context with prompts
+
+mod command;
+mod diff;
+mod files;
+
+use crate::estimate_token_count;
+use crate::history_file::FrontMatter;
+use colored::Colorize;
+pub(crate) use command::CommandLimits;
+use command::CommandStatus;
+use files::is_glob;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::{env, fs, io, path};
+
+/// A change to the context set, see the `:context` command
+pub enum ContextChange {
+    /// Replace the context set with the given entries
+    Set(Vec<ContextEntry>),
+    Add(Vec<ContextEntry>),
+    /// Entries to remove, either as a path, as a command starting with `!` or as the number shown by `List`
+    Remove(Vec<String>),
+    /// Change the mode of an entry, found like the entries to remove
+    SetMode(String, ContextMode),
+    List,
+    Clear,
+}
+
+/// How the content of a context entry is sent
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ContextMode {
+    /// Sent in full with each prompt without being recorded in the history
+    #[default]
+    Live,
+    /// Recorded in the history with the next prompt, after which the entry is removed
+    Once,
+    /// Recorded in the history in full with the next prompt,
+    /// after that only a diff since the last recorded content is recorded
+    Diff,
+}
+
+impl ContextMode {
+    /// Split a `once:` or `diff:` prefix from an entry, entries without a prefix are live
+    pub(crate) fn split(entry: &str) -> (ContextMode, &str) {
+        for mode in [ContextMode::Live, ContextMode::Once, ContextMode::Diff] {
+            if let Some(rest) = entry.strip_prefix(&format!("{mode}:")) {
+                return (mode, rest);
+            }
+        }
+        (ContextMode::Live, entry)
+    }
+
+    /// The prefix used on the command line and in the front matter
+    fn prefix(&self) -> String {
+        match self {
+            ContextMode::Live => String::new(),
+            mode => format!("{mode}:"),
+        }
+    }
+}
+
+impl Display for ContextMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContextMode::Live => write!(f, "live"),
+            ContextMode::Once => write!(f, "once"),
+            ContextMode::Diff => write!(f, "diff"),
+        }
+    }
+}
+
+impl FromStr for ContextMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "live" => Ok(ContextMode::Live),
+            "once" => Ok(ContextMode::Once),
+            "diff" => Ok(ContextMode::Diff),
+            _ => Err(format!(
+                "Unknown context mode {mode}, expected live, once or diff"
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContextSource {
+    /// A file, directory or glob, expanded each time the context is loaded
+    Path(PathBuf),
+    /// A shell command, run each time the context is loaded
+    Command(String),
+}
+
+impl ContextSource {
+    /// Paths are made absolute so that the entries do not depend on the working directory
+    fn normalized(self) -> Self {
+        match self {
+            ContextSource::Path(path) => ContextSource::Path(absolute_entry(&path)),
+            ContextSource::Command(command) => ContextSource::Command(command.trim().to_string()),
+        }
+    }
+
+    fn is_expanded(&self) -> bool {
+        match self {
+            ContextSource::Path(path) => path.is_dir() || is_glob(&path.to_string_lossy()),
+            ContextSource::Command(_) => false,
+        }
+    }
+}
+
+impl Display for ContextSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContextSource::Path(path) => write!(f, "{}", display_path(path)),
+            ContextSource::Command(command) => write!(f, "!{command}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContextEntry {
+    pub(crate) source: ContextSource,
+    pub(crate) mode: ContextMode,
+}
+
+impl ContextEntry {
+    /// A path with an optional mode prefix, e.g. `diff:src/`
+    pub(crate) fn parse_path(entry: &str) -> Self {
+        let (mode, path) = ContextMode::split(entry);
+        ContextEntry {
+            source: ContextSource::Path(PathBuf::from(path)),
+            mode,
+        }
+    }
+
+    /// A command with an optional mode prefix, e.g. `diff:cargo test`
+    pub(crate) fn parse_command(entry: &str) -> Self {
+        let (mode, command) = ContextMode::split(entry);
+        ContextEntry {
+            source: ContextSource::Command(command.to_string()),
+            mode,
+        }
+    }
+
+    fn to_front_matter(&self) -> String {
+        match &self.source {
+            ContextSource::Path(path) => format!("{}{}", self.mode.prefix(), path.display()),
+            ContextSource::Command(command) => format!("{}{command}", self.mode.prefix()),
+        }
+    }
+}
+
+impl Display for ContextEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.mode.prefix(), self.source)
+    }
+}
+
+/// Files, directories, globs and commands whose content is sent with each prompt.
+/// Paths are stored as absolute paths and expanded each time the context is loaded,
+/// so that files added to a directory are picked up.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct ContextSet {
+    entries: Vec<ContextEntry>,
+    /// The content last recorded in the history for each file and command in the diff mode
+    snapshots: HashMap<String, String>,
+}
+
+impl ContextSet {
+    pub(crate) fn new(entries: impl IntoIterator<Item = ContextEntry>) -> Self {
+        let mut context = ContextSet::default();
+        for entry in entries {
+            context.add(entry);
+        }
+        context
+    }
+
+    /// Restore the entries recorded in the front matter of a history file.
+    /// The snapshots are not stored, so the first diff after opening a chat is the full content.
+    pub(crate) fn from_front_matter(front_matter: &FrontMatter) -> Self {
+        let files = front_matter
+            .context_files
+            .iter()
+            .map(|file| ContextEntry::parse_path(file));
+        let commands = front_matter
+            .context_commands
+            .iter()
+            .map(|command| ContextEntry::parse_command(command));
+        ContextSet::new(files.chain(commands))
+    }
+
+    pub(crate) fn apply_to_front_matter(&self, front_matter: &mut FrontMatter) {
+        front_matter.context_files.clear();
+        front_matter.context_commands.clear();
+
+        for entry in &self.entries {
+            match entry.source {
+                ContextSource::Path(_) => front_matter.context_files.push(entry.to_front_matter()),
+                ContextSource::Command(_) => {
+                    front_matter.context_commands.push(entry.to_front_matter())
+                }
+            }
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Add an entry, returns false if its file or command is already in the set
+    pub(crate) fn add(&mut self, entry: ContextEntry) -> bool {
+        let entry = ContextEntry {
+            source: entry.source.normalized(),
+            mode: entry.mode,
+        };
+        if self
+            .entries
+            .iter()
+            .any(|existing| existing.source == entry.source)
+        {
+            return false;
+        }
+        self.entries.push(entry);
+        true
+    }
+
+    /// Find an entry by its path, by its command starting with `!`
+    /// or by its number in the list, starting from 1
+    pub(crate) fn find(&self, entry: &str) -> Option<&ContextEntry> {
+        if let Ok(number) = entry.parse::<usize>() {
+            return number
+                .checked_sub(1)
+                .and_then(|index| self.entries.get(index));
+        }
+
+        let source = match entry.strip_prefix('!') {
+            Some(command) => ContextSource::Command(command.to_string()),
+            None => ContextSource::Path(PathBuf::from(entry)),
+        }
+        .normalized();
+        self.entries
+            .iter()
+            .find(|existing| existing.source == source)
+    }
+
+    /// Remove the entry of a file or command, returns false if it is not in the set
+    pub(crate) fn remove(&mut self, source: &ContextSource) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|existing| existing.source != *source);
+        self.entries.len() != len
+    }
+
+    /// Change the mode of an entry, returns the changed entry
+    pub(crate) fn set_mode(&mut self, entry: &str, mode: ContextMode) -> Option<&ContextEntry> {
+        let source = self.find(entry)?.source.clone();
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|existing| existing.source == source)?;
+        entry.mode = mode;
+        Some(entry)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Read the files and run the commands of all entries.
+    /// Files matched by several entries are only read once.
+    pub(crate) fn load(&self, limits: &CommandLimits) -> LoadedContext {
+        let mut seen = HashSet::new();
+        let mut items = Vec::new();
+
+        for entry in &self.entries {
+            for item in self.load_entry(entry, limits) {
+                if seen.insert(item.label.clone()) {
+                    items.push(item);
+                }
+            }
+        }
+
+        LoadedContext { items }
+    }
+
+    /// Called once the loaded context has been sent with a prompt. The content of the diff entries
+    /// becomes the snapshot the next diff is made against, and the entries sent once are removed.
+    pub(crate) fn mark_sent(&mut self, loaded: &LoadedContext) {
+        for item in &loaded.items {
+            if item.mode == ContextMode::Diff {
+                self.snapshots.insert(item.label.clone(), item.raw.clone());
+            }
+        }
+        self.entries.retain(|entry| entry.mode != ContextMode::Once);
+    }
+
+    /// Print the entries with the files they expand to and their estimated token counts
+    pub(crate) fn print_list(&self, limits: &CommandLimits) {
+        if self.entries.is_empty() {
+            println!("No context files, add them with :context add <path> or :context !<command>");
+            return;
+        }
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            let loaded = LoadedContext {
+                items: self.load_entry(entry, limits),
+            };
+
+            match (&entry.source, loaded.items.first()) {
+                (ContextSource::Command(_), Some(item)) => {
+                    println!("{}. {} ({})", index + 1, entry.source, item.describe())
+                }
+                _ => {
+                    let file_count = loaded.items.len();
+                    println!(
+                        "{}. {} ({}, {} file{}, ~{} tokens)",
+                        index + 1,
+                        entry.source,
+                        entry.mode,
+                        file_count,
+                        if file_count == 1 { "" } else { "s" },
+                        loaded.tokens()
+                    );
+                }
+            }
+
+            if entry.source.is_expanded() {
+                for item in &loaded.items {
+                    println!("     {} ({})", item.label, item.describe());
+                }
+            }
+        }
+    }
+
+    /// Read the files of a single entry or run its command. Files that are not text are skipped
+    /// silently when they were found in a directory or by a glob.
+    fn load_entry(&self, entry: &ContextEntry, limits: &CommandLimits) -> Vec<ContextItem> {
+        let loaded = match &entry.source {
+            ContextSource::Command(command) => match command::run(command, limits) {
+                Ok(output) => vec![(format!("$ {command}"), Some(output.status), output.output)],
+                Err(e) => {
+                    eprintln!("Error running context command {command}: {e}");
+                    Vec::new()
+                }
+            },
+            ContextSource::Path(path) => {
+                let explicit = !entry.source.is_expanded();
+                let paths = files::expand(path);
+                if paths.is_empty() {
+                    eprintln!("Context entry {} matches no files", entry.source);
+                }
+
+                paths
+                    .into_iter()
+                    .filter_map(|path| match fs::read_to_string(&path) {
+                        Ok(content) => Some((display_path(&path), None, content)),
+                        Err(e) if e.kind() == io::ErrorKind::InvalidData && !explicit => None,
+                        Err(e) => {
+                            eprintln!("Error reading context file {}: {e}", display_path(&path));
+                            None
+                        }
+                    })
+                    .collect()
+            }
+        };
+
+        loaded
+            .into_iter()
+            .map(|(label, status, raw)| {
+                let delta = match (entry.mode, self.snapshots.get(&label)) {
+                    (ContextMode::Diff, Some(snapshot)) if *snapshot == raw => Delta::Unchanged,
+                    (ContextMode::Diff, Some(snapshot)) => match diff::unified_diff(snapshot, &raw)
+                    {
+                        // A diff that is not shorter than the content is of no use
+                        Some(diff) if diff.len() < raw.len() => Delta::Diff(diff),
+                        _ => Delta::Full,
+                    },
+                    _ => Delta::Full,
+                };
+                ContextItem {
+                    label,
+                    status,
+                    mode: entry.mode,
+                    raw,
+                    delta,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Globs are not resolved against the file system, so they are only made absolute
+fn absolute_entry(entry: &Path) -> PathBuf {
+    if !is_glob(&entry.to_string_lossy())
+        && let Ok(canonical) = fs::canonicalize(entry)
+    {
+        return canonical;
+    }
+    path::absolute(entry).unwrap_or_else(|_| entry.to_path_buf())
+}
+
+/// Paths below the working directory are shown relative to it
+pub(crate) fn display_path(path: &Path) -> String {
+    env::current_dir()
+        .ok()
+        .and_then(|current_dir| path.strip_prefix(current_dir).ok())
+        .filter(|relative| !relative.as_os_str().is_empty())
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// What is sent of a file or the output of a command
+#[derive(Debug, PartialEq)]
+enum Delta {
+    Full,
+    /// Unified diff since the snapshot
+    Diff(String),
+    /// Nothing is sent, the snapshot is up to date
+    Unchanged,
+}
+
+/// A file or the output of a command
+pub(crate) struct ContextItem {
+    /// The path of a file or `$ <command>`
+    pub(crate) label: String,
+    /// Exit status of a command
+    status: Option<CommandStatus>,
+    mode: ContextMode,
+    raw: String,
+    delta: Delta,
+}
+
+impl ContextItem {
+    /// The content with a header naming the file or command, so that the model can tell them apart.
+    /// `None` if nothing is sent.
+    fn render(&self) -> Option<String> {
+        let mut notes: Vec<String> = self
+            .status
+            .iter()
+            .map(|status| status.to_string())
+            .collect();
+        let content = match &self.delta {
+            Delta::Full => self.raw.trim_end(),
+            Delta::Diff(diff) => {
+                notes.push("diff since the last time it was sent".to_string());
+                diff.trim_end()
+            }
+            Delta::Unchanged => return None,
+        };
+
+        let header = match notes.is_empty() {
+            true => self.label.clone(),
+            false => format!("{} ({})", self.label, notes.join(", ")),
+        };
+        Some(format!("==> {header} <==\n{content}\n"))
+    }
+
+    pub(crate) fn tokens(&self) -> usize {
+        self.render()
+            .map_or(0, |rendered| estimate_token_count(&rendered))
+    }
+
+    /// The mode, the exit status of a command and the estimated tokens, e.g. `diff, exit status 1, ~120 tokens`.
+    /// A failed exit status is shown in red.
+    pub(crate) fn describe(&self) -> String {
+        let mut parts = vec![self.mode.to_string()];
+        match &self.status {
+            Some(status) if status.success() => parts.push(status.to_string().green().to_string()),
+            Some(status) => parts.push(status.to_string().red().to_string()),
+            None => {}
+        }
+        match self.delta {
+            Delta::Unchanged => parts.push("unchanged".to_string()),
+            _ => parts.push(format!("~{} tokens", self.tokens())),
+        }
+        parts.join(", ")
+    }
+}
+
+pub(crate) struct LoadedContext {
+    pub(crate) items: Vec<ContextItem>,
+}
+
+impl LoadedContext {
+    /// The live content sent with the prompt without being recorded in the history,
+    /// `None` if there is nothing to send
+    pub(crate) fn live_content(&self) -> Option<String> {
+        self.content(|mode| mode == ContextMode::Live)
+    }
+
+    /// The content recorded in the history with the prompt, `None` if there is nothing to record
+    pub(crate) fn recorded_content(&self) -> Option<String> {
+        self.content(|mode| mode != ContextMode::Live)
+    }
+
+    fn content(&self, include: impl Fn(ContextMode) -> bool) -> Option<String> {
+        let rendered: Vec<String> = self
+            .items
+            .iter()
+            .filter(|item| include(item.mode))
+            .filter_map(ContextItem::render)
+            .collect();
+        (!rendered.is_empty()).then(|| rendered.join("\n"))
+    }
+
+    pub(crate) fn tokens(&self) -> usize {
+        self.items.iter().map(ContextItem::tokens).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn limits() -> CommandLimits {
+        CommandLimits {
+            timeout: Duration::from_secs(10),
+            max_output: 1000,
+        }
+    }
+
+    fn path(path: &Path, mode: ContextMode) -> ContextEntry {
+        ContextEntry {
+            source: ContextSource::Path(path.to_path_buf()),
+            mode,
+        }
+    }
+
+    #[test]
+    fn test_context_set_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes = temp_dir.path().join("notes.md");
+        let pattern = temp_dir.path().join("*.rs");
+        fs::write(&notes, "Notes").unwrap();
+
+        let mut context = ContextSet::new([
+            path(&notes, ContextMode::Live),
+            path(&notes, ContextMode::Once),
+        ]);
+        assert_eq!(context.entries.len(), 1);
+        assert!(context.add(path(&pattern, ContextMode::Diff)));
+        assert!(!context.add(path(&pattern, ContextMode::Live)));
+        assert!(context.add(ContextEntry::parse_command("once: git log -5 ")));
+
+        let notes = path(&fs::canonicalize(&notes).unwrap(), ContextMode::Live);
+        let pattern = path(&pattern, ContextMode::Diff);
+        let command = ContextEntry {
+            source: ContextSource::Command("git log -5".to_string()),
+            mode: ContextMode::Once,
+        };
+        assert_eq!(context.find("1"), Some(&notes));
+        assert_eq!(context.find(&pattern.source.to_string()), Some(&pattern));
+        assert_eq!(context.find("!git log -5"), Some(&command));
+        assert_eq!(context.find("4"), None);
+        assert_eq!(context.find("0"), None);
+
+        let mut front_matter = FrontMatter::default();
+        context.apply_to_front_matter(&mut front_matter);
+        assert_eq!(
+            front_matter.context_commands,
+            vec!["once:git log -5".to_string()]
+        );
+        assert_eq!(
+            front_matter.context_files[1],
+            format!("diff:{}", temp_dir.path().join("*.rs").display())
+        );
+        assert_eq!(ContextSet::from_front_matter(&front_matter), context);
+
+        assert!(context.remove(&notes.source));
+        assert!(!context.remove(&notes.source));
+        assert_eq!(
+            context
+                .set_mode("!git log -5", ContextMode::Live)
+                .map(|entry| entry.mode),
+            Some(ContextMode::Live)
+        );
+        assert_eq!(context.entries.len(), 2);
+    }
+
+    #[test]
+    fn test_context_mode() {
+        assert_eq!(ContextMode::split("diff:src/"), (ContextMode::Diff, "src/"));
+        assert_eq!(ContextMode::split("once:!ls"), (ContextMode::Once, "!ls"));
+        assert_eq!(ContextMode::split("live:a.md"), (ContextMode::Live, "a.md"));
+        assert_eq!(
+            ContextMode::split("notes:a.md"),
+            (ContextMode::Live, "notes:a.md")
+        );
+        assert_eq!("diff".parse::<ContextMode>(), Ok(ContextMode::Diff));
+        assert!("always".parse::<ContextMode>().is_err());
+    }
+
+    #[test]
+    fn test_load_context() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("notes.md"), "Some notes\n").unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(root.join("src/image.png"), [0xff, 0xfe, 0x00]).unwrap();
+
+        let context = ContextSet::new([
+            path(&root.join("notes.md"), ContextMode::Live),
+            path(&root.join("src"), ContextMode::Live),
+            path(&root.join("src/*.rs"), ContextMode::Live),
+        ]);
+        let loaded = context.load(&limits());
+
+        // Binary files in directories are skipped and files are only included once
+        assert_eq!(loaded.items.len(), 2);
+        let content = loaded.live_content().unwrap();
+        let root = fs::canonicalize(root).unwrap();
+        assert_eq!(
+            content,
+            format!(
+                "==> {} <==\nSome notes\n\n==> {} <==\nfn main() {{}}\n",
+                root.join("notes.md").display(),
+                root.join("src/main.rs").display()
+            )
+        );
+        assert_eq!(loaded.recorded_content(), None);
+        assert_eq!(
+            loaded.tokens(),
+            loaded.items.iter().map(ContextItem::tokens).sum::<usize>()
+        );
+
+        assert!(
+            ContextSet::default()
+                .load(&limits())
+                .live_content()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_once_and_diff_modes() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes = fs::canonicalize(temp_dir.path()).unwrap().join("notes.md");
+        let code = notes.with_file_name("code.rs");
+        let lines: String = (1..=20).map(|i| format!("line {i}\n")).collect();
+        fs::write(&notes, "Notes\n").unwrap();
+        fs::write(&code, &lines).unwrap();
+
+        let mut context = ContextSet::new([
+            path(&notes, ContextMode::Once),
+            path(&code, ContextMode::Diff),
+        ]);
+
+        // Both are recorded in full the first time, after which the entry sent once is removed
+        let loaded = context.load(&limits());
+        assert_eq!(loaded.live_content(), None);
+        assert_eq!(
+            loaded.recorded_content().unwrap(),
+            format!(
+                "==> {} <==\nNotes\n\n==> {} <==\n{}",
+                notes.display(),
+                code.display(),
+                lines
+            )
+        );
+        context.mark_sent(&loaded);
+        assert_eq!(context.entries, [path(&code, ContextMode::Diff)]);
+
+        let loaded = context.load(&limits());
+        assert_eq!(loaded.recorded_content(), None);
+        assert!(loaded.items[0].describe().ends_with("unchanged"));
+        context.mark_sent(&loaded);
+
+        fs::write(&code, lines.replace("line 10\n", "line ten\n")).unwrap();
+        let loaded = context.load(&limits());
+        assert_eq!(
+            loaded.recorded_content().unwrap(),
+            format!(
+                "==> {} (diff since the last time it was sent) <==\n\
+                 @@ -7,7 +7,7 @@\n line 7\n line 8\n line 9\n-line 10\n+line ten\n line 11\n line 12\n line 13\n",
+                code.display()
+            )
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_load_command_context() {
+        let context = ContextSet::new([ContextEntry::parse_command("echo failed >&2; exit 1")]);
+        let loaded = context.load(&limits());
+
+        assert_eq!(
+            loaded.live_content().unwrap(),
+            "==> $ echo failed >&2; exit 1 (exit status 1) <==\nfailed\n"
+        );
+    }
+}
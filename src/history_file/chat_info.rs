@@ -0,0 +1,118 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use super::storage::storage_for;
+use super::{ChatIndex, HistoryFile};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Maximum number of characters in a title taken from the first user message
+const TITLE_LENGTH: usize = 60;
+
+/// Metadata of a history file shown by `:list`
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ChatInfo {
+    pub(crate) path: PathBuf,
+    pub(crate) modified: Option<SystemTime>,
+    /// File size in bytes
+    pub(crate) size: u64,
+    pub(crate) message_count: usize,
+    pub(crate) tokens: usize,
+    /// Title from the front matter or the chat index, or the start of the first user message
+    pub(crate) title: Option<String>,
+    /// History file this chat was forked from
+    pub(crate) parent: Option<PathBuf>,
+}
+
+impl ChatInfo {
+    /// Read the metadata of a history file, files that can't be read as chats only have file metadata
+    pub(crate) fn read(path: &Path, index: &ChatIndex) -> ChatInfo {
+        let metadata = fs::metadata(path).ok();
+        let (front_matter, content) = storage_for(path).read(path).unwrap_or_default();
+        let front_matter = front_matter.unwrap_or_default();
+        let messages = HistoryFile::parse_messages(&content);
+
+        let title = front_matter
+            .title
+            .or_else(|| index.get(path).map(|entry| entry.title.clone()))
+            .filter(|title| !title.trim().is_empty())
+            .or_else(|| {
+            messages
+                .iter()
+                .find(|(role, _)| *role == "user")
+                .and_then(|(_, message)| message.lines().find(|line| !line.trim().is_empty()))
+                .map(|line| shorten(line.trim(), TITLE_LENGTH))
+        });
+
+        ChatInfo {
+            path: path.to_path_buf(),
+            modified: metadata.as_ref().and_then(|metadata| metadata.modified().ok()),
+            size: metadata.map(|metadata| metadata.len()).unwrap_or_default(),
+            message_count: messages.len(),
+            tokens: crate::estimate_token_count(&content),
+            title,
+            parent: front_matter.parent.map(PathBuf::from),
+        }
+    }
+}
+
+fn shorten(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let shortened: String = text.chars().take(max_chars).collect();
+    format!("{}...", shortened.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history_file::IndexEntry;
+
+    #[test]
+    fn test_read_chat_info() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let forked = temp_dir.path().join("forked.md");
+        let plain = temp_dir.path().join("plain.md");
+        fs::write(
+            &forked,
+            "+++\nparent = \"/data/parent.md\"\ntitle = \"Lifetimes\"\n+++\nQuestion",
+        )
+        .unwrap();
+        fs::write(&plain, format!("\n{}\nSecond line", "a".repeat(70))).unwrap();
+
+        let mut index = ChatIndex::default();
+        let info = ChatInfo::read(&forked, &index);
+        assert_eq!(info.parent, Some(PathBuf::from("/data/parent.md")));
+        assert_eq!(info.title, Some("Lifetimes".to_string()));
+        assert_eq!(info.message_count, 1);
+        assert_eq!(info.tokens, 3);
+        assert!(info.modified.is_some());
+
+        let info = ChatInfo::read(&plain, &index);
+        assert_eq!(info.parent, None);
+        assert_eq!(info.title, Some(format!("{}...", "a".repeat(60))));
+        assert_eq!(info.size, 83);
+
+        index.set(&plain, IndexEntry { title: "Indexed".to_string(), summary: None });
+        assert_eq!(ChatInfo::read(&plain, &index).title, Some("Indexed".to_string()));
+
+        let info = ChatInfo::read(&temp_dir.path().join("missing.md"), &index);
+        assert_eq!((info.message_count, info.title, info.modified), (0, None, None));
+    }
+}
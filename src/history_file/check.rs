@@ -0,0 +1,263 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use super::storage::jsonl_storage::JsonlStorage;
+use super::{FrontMatter, MessageMetadata, DELIMITER_REGEX, FORMAT_VERSION, SEPARATOR_LINE_REGEX};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::path::Path;
+use std::{fs, io};
+
+lazy_static! {
+    /// Anything that looks like a delimiter, known or not
+    static ref BANNER_REGEX: Regex = Regex::new(r"(?m)^-{67}\n\s*--- (.*) ---\n(?:[^\n-][^\n]*\n)?-{67}$")
+        .expect("Failed to compile regex pattern");
+}
+
+/// A problem found in a history file
+#[derive(Debug, PartialEq)]
+pub(crate) struct Issue {
+    /// 1-based line number in the history file
+    pub(crate) line: usize,
+    pub(crate) description: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct CheckReport {
+    /// Format version of the file, `None` for the original format
+    pub(crate) version: Option<u32>,
+    pub(crate) issues: Vec<Issue>,
+}
+
+/// Check a history file in either format for ambiguous or corrupt segments
+pub(crate) fn check_file(path: &Path) -> io::Result<CheckReport> {
+    if path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("jsonl"))
+    {
+        // Message boundaries are exact in JSON lines, only the records themselves can be invalid
+        let issues = fs::read_to_string(path)?
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                JsonlStorage::line_error(line).map(|description| Issue {
+                    line: index + 1,
+                    description,
+                })
+            })
+            .collect();
+        return Ok(CheckReport {
+            version: Some(FORMAT_VERSION),
+            issues,
+        });
+    }
+
+    Ok(check(&fs::read_to_string(path)?))
+}
+
+/// Check the raw content of a history file for ambiguous or corrupt segments
+pub(crate) fn check(raw: &str) -> CheckReport {
+    let mut issues = Vec::new();
+    let (front_matter, content) = FrontMatter::split(raw);
+    let offset = raw.len() - content.len();
+    let line_of = |index: usize| raw[..offset + index].matches('\n').count() + 1;
+
+    if front_matter.is_none() && raw.starts_with("+++") {
+        issues.push(Issue {
+            line: 1,
+            description: "front matter could not be parsed".to_string(),
+        });
+    }
+
+    let version = front_matter.and_then(|front_matter| front_matter.version);
+    if version.is_some_and(|version| version > FORMAT_VERSION) {
+        issues.push(Issue {
+            line: 1,
+            description: format!("unsupported history format version {}", version.unwrap_or_default()),
+        });
+        return CheckReport { version, issues };
+    }
+
+    let mut unknown_banners = Vec::new();
+    for banner in BANNER_REGEX.captures_iter(content) {
+        let delimiter = banner.get(0).unwrap();
+        let known = DELIMITER_REGEX
+            .find_iter(content)
+            .any(|known| known.start() <= delimiter.start() && delimiter.end() <= known.end());
+        if !known {
+            issues.push(Issue {
+                line: line_of(delimiter.start()),
+                description: format!("unknown delimiter '{}'", &banner[1]),
+            });
+            unknown_banners.push(delimiter.range());
+        }
+    }
+
+    for delimiter in DELIMITER_REGEX.captures_iter(content) {
+        // Group 1 is the whole delimiter, the metadata line is in one of the following groups
+        if let Some(line) = delimiter.iter().skip(2).flatten().next()
+            && let Err(error) = MessageMetadata::parse(line.as_str())
+        {
+            issues.push(Issue {
+                line: line_of(line.start()),
+                description: format!("invalid message metadata, {error}"),
+            });
+        }
+    }
+
+    let mut segment_start = 0;
+    let mut delimiters = DELIMITER_REGEX.find_iter(content).peekable();
+    let mut is_first = true;
+    loop {
+        let segment_end = delimiters.peek().map(|d| d.start()).unwrap_or(content.len());
+        let segment = &content[segment_start..segment_end];
+
+        if !is_first && segment.trim().is_empty() {
+            issues.push(Issue {
+                line: line_of(segment_start),
+                description: "empty message".to_string(),
+            });
+        }
+
+        for separator in SEPARATOR_LINE_REGEX.find_iter(segment) {
+            let position = segment_start + separator.start();
+            let in_unknown_banner = unknown_banners.iter().any(|banner| banner.contains(&position));
+
+            if !separator.as_str().starts_with('\\') && !in_unknown_banner {
+                let description = if version.is_some() {
+                    "unescaped separator line in message"
+                } else {
+                    "separator line in message, the message may have been split"
+                };
+                issues.push(Issue {
+                    line: line_of(position),
+                    description: description.to_string(),
+                });
+            }
+        }
+
+        match delimiters.next() {
+            Some(delimiter) => segment_start = delimiter.end(),
+            None => break,
+        }
+        is_first = false;
+    }
+
+    issues.sort_by_key(|issue| issue.line);
+    CheckReport { version, issues }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history_file::{HistoryFile, DELIMITER_AI_RESPONSE, DELIMITER_USER_INPUT};
+
+    #[test]
+    fn test_check_valid_file() {
+        let raw = format!(
+            "+++\nversion = 2\n+++\n{}Question{}Answer\n\\{}",
+            DELIMITER_USER_INPUT,
+            DELIMITER_AI_RESPONSE,
+            "-".repeat(67)
+        );
+
+        let report = check(&raw);
+
+        assert_eq!(report.version, Some(2));
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn test_check_reports_problems() {
+        let raw = format!(
+            "{}Question{}{}Answer\n{}\nMore{}",
+            DELIMITER_USER_INPUT,
+            DELIMITER_AI_RESPONSE,
+            DELIMITER_AI_RESPONSE,
+            "-".repeat(67),
+            DELIMITER_USER_INPUT.replace("User Input", "Something Else")
+        );
+
+        let report = check(&raw);
+
+        assert_eq!(report.version, None);
+        let descriptions: Vec<&str> = report
+            .issues
+            .iter()
+            .map(|issue| issue.description.as_str())
+            .collect();
+        assert_eq!(
+            descriptions,
+            vec![
+                "empty message",
+                "separator line in message, the message may have been split",
+                "unknown delimiter 'Something Else'",
+            ]
+        );
+        assert_eq!(report.issues[0].line, 11);
+    }
+
+    #[test]
+    fn test_check_message_metadata() {
+        let metadata = MessageMetadata {
+            model: Some("gemma3:12b".to_string()),
+            ..Default::default()
+        };
+        let raw = format!(
+            "+++\nversion = 3\n+++\n{}{}{}",
+            HistoryFile::render_message("user", "Question", Some(&metadata)).unwrap(),
+            HistoryFile::render_message("assistant", "Answer", Some(&metadata)).unwrap(),
+            DELIMITER_USER_INPUT.replace("--- User Input ---\n", "--- User Input ---\ninput_tokens: many\n"),
+        );
+
+        let report = check(&format!("{raw}More"));
+
+        assert_eq!(
+            report.issues,
+            vec![Issue {
+                line: 20,
+                description: "invalid message metadata, invalid token count 'many'".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_check_jsonl_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("chat.jsonl");
+        fs::write(
+            &path,
+            "{\"role\":\"user\",\"content\":\"Hello\"}\n\nnot json\n{\"role\":\"robot\",\"content\":\"Beep\"}\n",
+        )
+        .unwrap();
+
+        let report = check_file(&path).unwrap();
+
+        assert_eq!(report.version, Some(FORMAT_VERSION));
+        assert_eq!(report.issues.len(), 2);
+        assert_eq!(report.issues[0].line, 3);
+        assert_eq!(report.issues[1].line, 4);
+        assert_eq!(report.issues[1].description, "unknown role 'robot'");
+    }
+
+    #[test]
+    fn test_check_unsupported_version() {
+        let report = check("+++\nversion = 99\n+++\n");
+
+        assert_eq!(report.version, Some(99));
+        assert_eq!(report.issues[0].description, "unsupported history format version 99");
+    }
+}
@@ -0,0 +1,311 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use super::MessageMetadata;
+use serde_json::Value;
+use std::path::Path;
+use std::{fs, io};
+
+/// Role, content and metadata of an exported message
+type ExportMessage<'a> = (&'a str, &'a str, Option<MessageMetadata>);
+
+/// Formats a chat can be exported to for sharing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ExportFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+impl ExportFormat {
+    pub(crate) fn parse_export_format(format: &str) -> Result<ExportFormat, String> {
+        match format.to_lowercase().as_str() {
+            "md" | "markdown" => Ok(ExportFormat::Markdown),
+            "html" => Ok(ExportFormat::Html),
+            "json" => Ok(ExportFormat::Json),
+            _ => Err(format!("Invalid export format: {format}, expected md, html or json")),
+        }
+    }
+
+    /// Render the parsed messages of a chat and their metadata, see `HistoryFile::stored_content_json`
+    pub(crate) fn render(&self, title: &str, messages: &Value) -> io::Result<String> {
+        let messages: Vec<ExportMessage> = messages
+            .as_array()
+            .map(|messages| {
+                messages
+                    .iter()
+                    .map(|message| {
+                        (
+                            message["role"].as_str().unwrap_or_default(),
+                            message["content"].as_str().unwrap_or_default(),
+                            serde_json::from_value(message["metadata"].clone()).ok(),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        match self {
+            ExportFormat::Markdown => Ok(render_markdown(title, &messages)),
+            ExportFormat::Html => Ok(render_html(title, &messages)),
+            ExportFormat::Json => {
+                let messages: Vec<Value> = messages
+                    .iter()
+                    .map(|(role, content, metadata)| {
+                        let mut message = serde_json::json!({ "role": role, "content": content });
+                        if let Some(metadata) = metadata {
+                            message["metadata"] = serde_json::json!(metadata);
+                        }
+                        message
+                    })
+                    .collect();
+                Ok(format!("{}\n", serde_json::to_string_pretty(&messages)?))
+            }
+        }
+    }
+}
+
+/// Write an export to a new file, fails if the destination already exists and is not empty
+pub(crate) fn write_export(destination: &Path, content: &str) -> io::Result<()> {
+    if destination.metadata().is_ok_and(|m| m.len() > 0) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", destination.display()),
+        ));
+    }
+
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(destination, content)
+}
+
+fn role_heading(role: &str) -> &str {
+    match role {
+        "user" => "User",
+        "assistant" => "Assistant",
+        "tool" => "Tool",
+        _ => role,
+    }
+}
+
+/// Tool blocks usually contain the JSON of the tool calls, which is pretty-printed
+fn tool_json(role: &str, content: &str) -> Option<String> {
+    if role != "tool" {
+        return None;
+    }
+
+    serde_json::from_str::<Value>(content)
+        .ok()
+        .and_then(|json| serde_json::to_string_pretty(&json).ok())
+}
+
+/// Messages are written as is under role headings, so code fences are kept
+fn render_markdown(title: &str, messages: &[ExportMessage]) -> String {
+    let mut markdown = format!("# {title}\n");
+
+    for (role, content, metadata) in messages {
+        markdown.push_str(&format!("\n## {}\n\n", role_heading(role)));
+        if let Some(metadata) = metadata {
+            markdown.push_str(&format!("_{}_\n\n", metadata.describe()));
+        }
+        match tool_json(role, content) {
+            Some(json) => markdown.push_str(&format!("```json\n{json}\n```\n")),
+            None => markdown.push_str(&format!("{content}\n")),
+        }
+    }
+
+    markdown
+}
+
+const HTML_STYLE: &str = "body { max-width: 50rem; margin: 2rem auto; padding: 0 1rem; font-family: sans-serif; line-height: 1.5; }
+.message { margin: 1rem 0; padding: 0.5rem 1rem; border-left: 4px solid #999; }
+.message.user { border-color: #3b82f6; }
+.message.assistant { border-color: #10b981; }
+.message.tool { border-color: #f59e0b; }
+.metadata { color: #6b7280; font-size: 0.875rem; }
+pre { padding: 0.75rem; overflow-x: auto; background: #f3f4f6; }
+code { font-family: monospace; }";
+
+/// A self-contained page, code blocks use `language-*` classes that highlighters such as highlight.js recognize
+fn render_html(title: &str, messages: &[ExportMessage]) -> String {
+    let title = escape_html(title);
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n{HTML_STYLE}\n</style>\n</head>\n<body>\n<h1>{title}</h1>\n"
+    );
+
+    for (role, content, metadata) in messages {
+        html.push_str(&format!(
+            "<section class=\"message {}\">\n<h2>{}</h2>\n",
+            escape_html(role),
+            escape_html(role_heading(role))
+        ));
+        if let Some(metadata) = metadata {
+            html.push_str(&format!("<p class=\"metadata\">{}</p>\n", escape_html(&metadata.describe())));
+        }
+        match tool_json(role, content) {
+            Some(json) => html.push_str(&format!(
+                "<pre><code class=\"language-json\">{}</code></pre>\n",
+                escape_html(&json)
+            )),
+            None => html.push_str(&text_to_html(content)),
+        }
+        html.push_str("</section>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Convert code fences to code blocks and the rest to paragraphs with inline code
+fn text_to_html(content: &str) -> String {
+    let mut html = String::new();
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut in_code_block = false;
+
+    for line in content.lines() {
+        let fence = line.trim_start().strip_prefix("```");
+
+        if in_code_block {
+            if fence.is_some_and(|rest| rest.trim().is_empty()) {
+                html.push_str("</code></pre>\n");
+                in_code_block = false;
+            } else {
+                html.push_str(&escape_html(line));
+                html.push('\n');
+            }
+        } else if let Some(language) = fence {
+            flush_paragraph(&mut html, &mut paragraph);
+            let language = language.trim();
+            if language.is_empty() {
+                html.push_str("<pre><code>");
+            } else {
+                html.push_str(&format!("<pre><code class=\"language-{}\">", escape_html(language)));
+            }
+            in_code_block = true;
+        } else if line.trim().is_empty() {
+            flush_paragraph(&mut html, &mut paragraph);
+        } else {
+            paragraph.push(inline_code(line));
+        }
+    }
+
+    if in_code_block {
+        html.push_str("</code></pre>\n");
+    }
+    flush_paragraph(&mut html, &mut paragraph);
+
+    html
+}
+
+fn flush_paragraph(html: &mut String, paragraph: &mut Vec<String>) {
+    if !paragraph.is_empty() {
+        html.push_str(&format!("<p>{}</p>\n", paragraph.join("<br>\n")));
+        paragraph.clear();
+    }
+}
+
+/// Text between pairs of backticks is wrapped in `<code>`, unpaired backticks are left as is
+fn inline_code(line: &str) -> String {
+    let parts: Vec<&str> = line.split('`').collect();
+    if parts.len().is_multiple_of(2) {
+        return escape_html(line);
+    }
+
+    parts
+        .iter()
+        .enumerate()
+        .map(|(i, part)| {
+            if i % 2 == 1 {
+                format!("<code>{}</code>", escape_html(part))
+            } else {
+                escape_html(part)
+            }
+        })
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages() -> Value {
+        serde_json::json!([
+            { "role": "user", "content": "How do I print in Rust?" },
+            {
+                "role": "assistant",
+                "content": "Use `println!`:\n\n```rust\nprintln!(\"<hi>\");\n```",
+                "metadata": { "profile": "local", "model": "gemma3:12b" }
+            },
+            { "role": "tool", "content": "[{\"tool_name\":\"cat\",\"tool_result\":\"ok\"}]" },
+        ])
+    }
+
+    #[test]
+    fn test_parse_export_format() {
+        assert_eq!(ExportFormat::parse_export_format("MD"), Ok(ExportFormat::Markdown));
+        assert_eq!(ExportFormat::parse_export_format("markdown"), Ok(ExportFormat::Markdown));
+        assert_eq!(ExportFormat::parse_export_format("html"), Ok(ExportFormat::Html));
+        assert_eq!(ExportFormat::parse_export_format("json"), Ok(ExportFormat::Json));
+        assert!(ExportFormat::parse_export_format("pdf").is_err());
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let markdown = ExportFormat::Markdown.render("chat.md", &messages()).unwrap();
+        assert_eq!(
+            markdown,
+            "# chat.md\n\n## User\n\nHow do I print in Rust?\n\n## Assistant\n\n_gemma3:12b (local)_\n\nUse `println!`:\n\n```rust\nprintln!(\"<hi>\");\n```\n\n## Tool\n\n```json\n[\n  {\n    \"tool_name\": \"cat\",\n    \"tool_result\": \"ok\"\n  }\n]\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_render_html() {
+        let html = ExportFormat::Html.render("<chat>", &messages()).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>&lt;chat&gt;</title>"));
+        assert!(html.contains("<section class=\"message user\">\n<h2>User</h2>\n<p>How do I print in Rust?</p>\n</section>"));
+        assert!(html.contains("<h2>Assistant</h2>\n<p class=\"metadata\">gemma3:12b (local)</p>\n"));
+        assert!(html.contains("<p>Use <code>println!</code>:</p>\n<pre><code class=\"language-rust\">println!(&quot;&lt;hi&gt;&quot;);\n</code></pre>\n"));
+        assert!(html.contains("<pre><code class=\"language-json\">[\n  {\n    &quot;tool_name&quot;"));
+        assert!(html.ends_with("</html>\n"));
+    }
+
+    #[test]
+    fn test_render_json() {
+        let json = ExportFormat::Json.render("chat.md", &messages()).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), messages());
+    }
+
+    #[test]
+    fn test_write_export_existing_destination() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = temp_dir.path().join("exports/chat.md");
+
+        write_export(&destination, "First").unwrap();
+        assert!(write_export(&destination, "Second").is_err());
+        assert_eq!(fs::read_to_string(&destination).unwrap(), "First");
+    }
+}
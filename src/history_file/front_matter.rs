@@ -0,0 +1,227 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::config::profiles_config::ModelType;
+use serde::{Deserialize, Serialize};
+
+static FRONT_MATTER_DELIMITER: &str = "+++";
+
+/// Session settings stored as a TOML header at the top of a history file.
+///
+/// ```text
+/// +++
+/// profile = "local"
+/// model_type = "deep"
+/// +++
+/// ```
+///
+/// The header is never sent to the model.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub(crate) struct FrontMatter {
+    /// History format version, files without a version use the original format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) version: Option<u32>,
+
+    /// Title shown by `:list`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) title: Option<String>,
+
+    /// One-line summary of the chat
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) summary: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) profile: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) model_type: Option<ModelType>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) system_prompt: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) context_files: Vec<String>,
+
+    /// Shell commands whose output is sent as context, see `:context !<command>`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) context_commands: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) tools: Option<Vec<String>>,
+
+    /// Message numbers that are always sent, even when older messages no longer fit the context
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) pinned: Vec<usize>,
+
+    /// History file this chat was forked from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) parent: Option<String>,
+
+    /// Number of parent messages included in the fork
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) forked_at: Option<usize>,
+
+    /// History files forked from this chat
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) children: Vec<String>,
+
+    /// Export file an imported chat was read from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) imported_from: Option<String>,
+
+    /// Creation time of an imported chat (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) created: Option<String>,
+}
+
+impl FrontMatter {
+    /// Split raw file content into the front matter and the remaining chat content.
+    ///
+    /// Content without a (valid) front matter block is returned as is.
+    pub(crate) fn split(raw: &str) -> (Option<FrontMatter>, &str) {
+        let Some(rest) = raw
+            .strip_prefix(FRONT_MATTER_DELIMITER)
+            .and_then(|r| r.strip_prefix("\r\n").or_else(|| r.strip_prefix('\n')))
+        else {
+            return (None, raw);
+        };
+
+        let closing = format!("\n{FRONT_MATTER_DELIMITER}");
+        let mut search_start = 0;
+        while let Some(found) = rest[search_start..].find(&closing) {
+            let header_end = search_start + found;
+            let after = &rest[header_end + closing.len()..];
+
+            let body = if after.is_empty() {
+                Some(after)
+            } else {
+                after
+                    .strip_prefix("\r\n")
+                    .or_else(|| after.strip_prefix('\n'))
+            };
+
+            if let Some(body) = body {
+                return match toml::from_str::<FrontMatter>(&rest[..header_end]) {
+                    Ok(front_matter) => (Some(front_matter), body),
+                    Err(e) => {
+                        eprintln!("Failed to parse front matter: {e}");
+                        (None, raw)
+                    }
+                };
+            }
+
+            search_start = header_end + closing.len();
+        }
+
+        (None, raw)
+    }
+
+    /// Render the front matter block, including the delimiters and a trailing newline
+    pub(crate) fn render(&self) -> String {
+        let toml_str = toml::to_string(self).unwrap_or_else(|e| {
+            eprintln!("Failed to serialize front matter: {e}");
+            String::new()
+        });
+
+        format!("{FRONT_MATTER_DELIMITER}\n{toml_str}{FRONT_MATTER_DELIMITER}\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_without_front_matter() {
+        let raw = "Just some content";
+        let (front_matter, content) = FrontMatter::split(raw);
+
+        assert!(front_matter.is_none());
+        assert_eq!(content, raw);
+    }
+
+    #[test]
+    fn test_split_with_front_matter() {
+        let raw = "+++\nprofile = \"remote\"\nmodel_type = \"deep\"\ncontext_files = [\"notes.md\"]\n+++\nContent";
+        let (front_matter, content) = FrontMatter::split(raw);
+
+        let front_matter = front_matter.unwrap();
+        assert_eq!(front_matter.profile, Some("remote".to_string()));
+        assert_eq!(front_matter.model_type, Some(ModelType::Deep));
+        assert_eq!(front_matter.context_files, vec!["notes.md".to_string()]);
+        assert_eq!(front_matter.system_prompt, None);
+        assert_eq!(front_matter.tools, None);
+        assert_eq!(content, "Content");
+    }
+
+    #[test]
+    fn test_split_front_matter_only() {
+        let (front_matter, content) = FrontMatter::split("+++\nprofile = \"local\"\n+++");
+
+        assert_eq!(front_matter.unwrap().profile, Some("local".to_string()));
+        assert_eq!(content, "");
+    }
+
+    #[test]
+    fn test_split_invalid_front_matter() {
+        let raw = "+++\nthis is not toml\n+++\nContent";
+        let (front_matter, content) = FrontMatter::split(raw);
+
+        assert!(front_matter.is_none());
+        assert_eq!(content, raw);
+    }
+
+    #[test]
+    fn test_split_unclosed_front_matter() {
+        let raw = "+++\nprofile = \"local\"\nContent";
+        let (front_matter, content) = FrontMatter::split(raw);
+
+        assert!(front_matter.is_none());
+        assert_eq!(content, raw);
+    }
+
+    #[test]
+    fn test_render_round_trip() {
+        let front_matter = FrontMatter {
+            version: Some(2),
+            title: Some("Lifetimes".to_string()),
+            summary: Some("How lifetimes work".to_string()),
+            profile: Some("local".to_string()),
+            model_type: Some(ModelType::Fast),
+            system_prompt: Some("Multi\nline \"prompt\"".to_string()),
+            context_files: vec!["a.md".to_string(), "b.md".to_string()],
+            context_commands: vec!["git log -5".to_string()],
+            tools: Some(vec!["grep".to_string()]),
+            pinned: vec![1, 4],
+            parent: Some("/tmp/parent.md".to_string()),
+            forked_at: Some(3),
+            children: vec!["/tmp/child.md".to_string()],
+            imported_from: Some("/tmp/export.json".to_string()),
+            created: Some("2024-05-01T12:00:00Z".to_string()),
+        };
+
+        let raw = format!("{}Content", front_matter.render());
+        let (parsed, content) = FrontMatter::split(&raw);
+
+        assert_eq!(parsed, Some(front_matter));
+        assert_eq!(content, "Content");
+    }
+
+    #[test]
+    fn test_render_empty() {
+        assert_eq!(FrontMatter::default().render(), "+++\n+++\n");
+    }
+}
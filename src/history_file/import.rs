@@ -0,0 +1,548 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use super::metadata::format_unix_timestamp;
+use super::{FrontMatter, HistoryFile, MessageMetadata};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Export formats of other tools that can be imported
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ImportFormat {
+    /// Ollama or OpenAI style message arrays, optionally wrapped in an object with `messages`
+    Messages,
+    /// `conversations.json` of a ChatGPT data export
+    ChatGpt,
+    /// `conversations.json` of a Claude data export
+    Claude,
+}
+
+impl ImportFormat {
+    pub(crate) fn parse_import_format(format: &str) -> Result<ImportFormat, String> {
+        match format.to_lowercase().as_str() {
+            "messages" | "ollama" | "openai" => Ok(ImportFormat::Messages),
+            "chatgpt" => Ok(ImportFormat::ChatGpt),
+            "claude" => Ok(ImportFormat::Claude),
+            _ => Err(format!(
+                "Invalid import format: {format}, expected messages, chatgpt or claude"
+            )),
+        }
+    }
+
+    /// Guess the format from the structure of the first conversation
+    fn detect(json: &Value) -> Option<ImportFormat> {
+        let first = match json {
+            Value::Array(items) => items.first()?,
+            _ => json,
+        };
+
+        if first.get("mapping").is_some() {
+            Some(ImportFormat::ChatGpt)
+        } else if first.get("chat_messages").is_some() {
+            Some(ImportFormat::Claude)
+        } else if first.get("role").is_some() || first.get("messages").is_some() {
+            Some(ImportFormat::Messages)
+        } else {
+            None
+        }
+    }
+}
+
+/// A conversation read from an export, ready to be written as a history file
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct ImportedChat {
+    title: Option<String>,
+    created: Option<String>,
+    system_prompt: Option<String>,
+    /// Role, content and the time the message was sent, if the export has it
+    messages: Vec<(&'static str, String, Option<String>)>,
+}
+
+/// Result of an import, skipped items are counted by reason
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct ImportReport {
+    pub(crate) files: Vec<PathBuf>,
+    pub(crate) message_count: usize,
+    pub(crate) skipped: BTreeMap<String, usize>,
+}
+
+impl ImportReport {
+    fn skip(&mut self, reason: impl Into<String>) {
+        *self.skipped.entry(reason.into()).or_default() += 1;
+    }
+}
+
+/// Import the conversations of an export file as history files in `cforge_dir`.
+///
+/// A single conversation is written to `<title>.md`, the conversations of an export with several
+/// conversations to `<source name>/<title>.md`.
+/// The format is detected from the content if not given.
+pub(crate) fn import_file(
+    source: &Path,
+    format: Option<ImportFormat>,
+    cforge_dir: &Path,
+) -> io::Result<ImportReport> {
+    let raw = fs::read_to_string(source)?;
+    let json: Value = serde_json::from_str(&raw).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is not valid JSON: {e}", source.display()),
+        )
+    })?;
+
+    let format = format.or_else(|| ImportFormat::detect(&json)).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Unrecognized export format in {}, choose one with --format",
+                source.display()
+            ),
+        )
+    })?;
+
+    let mut report = ImportReport::default();
+    let (chats, conversation_count) = parse_chats(&json, format, &mut report)?;
+
+    let source_name = source
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "import".to_string());
+    let directory = if conversation_count > 1 {
+        cforge_dir.join(slug(&source_name))
+    } else {
+        cforge_dir.to_path_buf()
+    };
+    let source_path = fs::canonicalize(source).unwrap_or_else(|_| source.to_path_buf());
+
+    for chat in chats {
+        let name = chat.title.as_deref().map(slug).filter(|name| !name.is_empty());
+        let destination = unique_path(&directory, &name.unwrap_or_else(|| slug(&source_name)));
+
+        let content: String = chat
+            .messages
+            .iter()
+            .filter_map(|(role, message, time)| {
+                let metadata = MessageMetadata {
+                    time: time.clone(),
+                    ..Default::default()
+                };
+                HistoryFile::render_message(role, message, Some(&metadata))
+            })
+            .collect();
+        let front_matter = FrontMatter {
+            system_prompt: chat.system_prompt,
+            imported_from: Some(source_path.display().to_string()),
+            created: chat.created,
+            ..Default::default()
+        };
+
+        HistoryFile::write_new(&destination, front_matter, &content)?;
+        report.message_count += chat.messages.len();
+        report.files.push(destination);
+    }
+
+    Ok(report)
+}
+
+/// Conversations with importable messages and the number of conversations in the export
+fn parse_chats(
+    json: &Value,
+    format: ImportFormat,
+    report: &mut ImportReport,
+) -> io::Result<(Vec<ImportedChat>, usize)> {
+    let conversations: Vec<&Value> = match (format, json) {
+        // A bare message array is a single conversation
+        (ImportFormat::Messages, Value::Array(items))
+            if items.first().is_none_or(|item| item.get("messages").is_none()) =>
+        {
+            vec![json]
+        }
+        (_, Value::Array(items)) => items.iter().collect(),
+        (_, Value::Object(_)) => vec![json],
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Expected a JSON array or object",
+            ));
+        }
+    };
+
+    let conversation_count = conversations.len();
+    let mut chats = Vec::new();
+    for conversation in conversations {
+        let chat = match format {
+            ImportFormat::Messages => parse_messages_chat(conversation, report),
+            ImportFormat::ChatGpt => parse_chatgpt_chat(conversation, report),
+            ImportFormat::Claude => parse_claude_chat(conversation, report),
+        };
+
+        if chat.messages.is_empty() {
+            report.skip("conversation without messages");
+        } else {
+            chats.push(chat);
+        }
+    }
+
+    Ok((chats, conversation_count))
+}
+
+fn parse_messages_chat(conversation: &Value, report: &mut ImportReport) -> ImportedChat {
+    let mut chat = ImportedChat {
+        title: string_field(conversation, &["title", "name"]),
+        created: timestamp_field(conversation, &["created_at", "created"]),
+        ..Default::default()
+    };
+
+    let messages = match conversation {
+        Value::Array(messages) => messages.as_slice(),
+        _ => conversation["messages"].as_array().map(Vec::as_slice).unwrap_or_default(),
+    };
+
+    for message in messages {
+        let content = text_content(&message["content"], report);
+        let time = timestamp_field(message, &["created_at", "timestamp", "created"]);
+        add_message(&mut chat, message["role"].as_str().unwrap_or_default(), content, time, report);
+    }
+
+    chat
+}
+
+/// Messages are stored as a tree, the conversation is the branch that ends at `current_node`
+fn parse_chatgpt_chat(conversation: &Value, report: &mut ImportReport) -> ImportedChat {
+    let mut chat = ImportedChat {
+        title: string_field(conversation, &["title"]),
+        created: timestamp_field(conversation, &["create_time"]),
+        ..Default::default()
+    };
+    let mapping = &conversation["mapping"];
+
+    let mut branch = Vec::new();
+    let mut node_id = conversation["current_node"].as_str();
+    while let Some(id) = node_id {
+        // Guard against cycles in a corrupt export
+        if branch.contains(&id) {
+            break;
+        }
+        branch.push(id);
+        node_id = mapping[id]["parent"].as_str();
+    }
+    branch.reverse();
+
+    for id in branch {
+        let message = &mapping[id]["message"];
+        if message.is_null() {
+            continue;
+        }
+
+        let content = &message["content"];
+        let text = match content["content_type"].as_str() {
+            Some("text") | Some("multimodal_text") => text_content(&content["parts"], report),
+            Some("code") => content["text"].as_str().unwrap_or_default().to_string(),
+            other => {
+                report.skip(format!("{} content", other.unwrap_or("unknown")));
+                continue;
+            }
+        };
+
+        add_message(
+            &mut chat,
+            message["author"]["role"].as_str().unwrap_or_default(),
+            text,
+            timestamp_field(message, &["create_time"]),
+            report,
+        );
+    }
+
+    chat
+}
+
+fn parse_claude_chat(conversation: &Value, report: &mut ImportReport) -> ImportedChat {
+    let mut chat = ImportedChat {
+        title: string_field(conversation, &["name"]),
+        created: timestamp_field(conversation, &["created_at"]),
+        ..Default::default()
+    };
+
+    for message in conversation["chat_messages"].as_array().map(Vec::as_slice).unwrap_or_default() {
+        // Newer exports keep the text in content blocks
+        let text = match message["text"].as_str() {
+            Some(text) if !text.trim().is_empty() => text.to_string(),
+            _ => text_content(&message["content"], report),
+        };
+        let time = timestamp_field(message, &["created_at"]);
+        add_message(&mut chat, message["sender"].as_str().unwrap_or_default(), text, time, report);
+    }
+
+    chat
+}
+
+fn add_message(
+    chat: &mut ImportedChat,
+    role: &str,
+    content: String,
+    time: Option<String>,
+    report: &mut ImportReport,
+) {
+    if content.trim().is_empty() {
+        report.skip("empty message");
+        return;
+    }
+
+    let role = match role {
+        "user" | "human" => "user",
+        "assistant" | "model" | "ai" => "assistant",
+        "tool" | "function" => "tool",
+        "system" => {
+            chat.system_prompt.get_or_insert(content);
+            return;
+        }
+        other => {
+            report.skip(format!("message with unsupported role '{other}'"));
+            return;
+        }
+    };
+
+    chat.messages.push((role, content.trim().to_string(), time));
+}
+
+/// Content is either a string or a list of parts, only text parts are imported
+fn text_content(content: &Value, report: &mut ImportReport) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| match part {
+                Value::String(text) => Some(text.as_str()),
+                _ => match part["text"].as_str() {
+                    Some(text) => Some(text),
+                    None => {
+                        report.skip("non-text content part");
+                        None
+                    }
+                },
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn string_field(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| value[*key].as_str())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(String::from)
+}
+
+/// Timestamps are either RFC 3339 strings, kept as is, or Unix timestamps
+fn timestamp_field(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter().find_map(|key| match &value[*key] {
+        Value::String(text) if !text.is_empty() => Some(text.clone()),
+        Value::Number(number) => number.as_f64().map(|seconds| format_unix_timestamp(seconds as i64)),
+        _ => None,
+    })
+}
+
+/// File name friendly version of a title
+fn slug(title: &str) -> String {
+    let slug = title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    slug.chars().take(60).collect::<String>().trim_end_matches('-').to_string()
+}
+
+/// `<name>.md` in the directory, numbered if the name is already taken
+fn unique_path(directory: &Path, name: &str) -> PathBuf {
+    let mut path = directory.join(format!("{name}.md"));
+    let mut number = 2;
+    while path.exists() {
+        path = directory.join(format!("{name}-{number}.md"));
+        number += 1;
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_import_format() {
+        assert_eq!(ImportFormat::parse_import_format("Ollama"), Ok(ImportFormat::Messages));
+        assert_eq!(ImportFormat::parse_import_format("chatgpt"), Ok(ImportFormat::ChatGpt));
+        assert_eq!(ImportFormat::parse_import_format("claude"), Ok(ImportFormat::Claude));
+        assert!(ImportFormat::parse_import_format("csv").is_err());
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("Rust: lifetimes & borrows?"), "rust-lifetimes-borrows");
+        assert_eq!(slug("   "), "");
+    }
+
+    #[test]
+    fn test_import_message_array() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("ollama.json");
+        fs::write(
+            &source,
+            serde_json::json!([
+                { "role": "system", "content": "Be brief" },
+                { "role": "user", "content": "Hello" },
+                { "role": "assistant", "content": [{ "type": "text", "text": "Hi" }, { "type": "image" }] },
+                { "role": "function", "content": "result" },
+                { "role": "user", "content": "" },
+            ])
+            .to_string(),
+        )
+        .unwrap();
+
+        let report = import_file(&source, None, temp_dir.path()).unwrap();
+        assert_eq!(report.files, vec![temp_dir.path().join("ollama.md")]);
+        assert_eq!(report.message_count, 3);
+        assert_eq!(
+            report.skipped,
+            BTreeMap::from([
+                ("empty message".to_string(), 1),
+                ("non-text content part".to_string(), 1),
+            ])
+        );
+
+        let (front_matter, content) = storage_for_test(&report.files[0]);
+        assert_eq!(front_matter.system_prompt, Some("Be brief".to_string()));
+        assert_eq!(
+            HistoryFile::parse_messages(&content),
+            vec![
+                ("user", "Hello".to_string()),
+                ("assistant", "Hi".to_string()),
+                ("tool", "result".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_chatgpt_export() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("conversations.json");
+        let message = |role: &str, text: &str| {
+            serde_json::json!({
+                "author": { "role": role },
+                "create_time": 1_714_564_860,
+                "content": { "content_type": "text", "parts": [text] }
+            })
+        };
+        fs::write(
+            &source,
+            serde_json::json!([
+                {
+                    "title": "Borrow checker",
+                    "create_time": 1_714_564_800.5,
+                    "current_node": "c",
+                    "mapping": {
+                        "root": { "message": null, "parent": null },
+                        "a": { "message": message("user", "Why?"), "parent": "root" },
+                        "b": { "message": message("assistant", "Old answer"), "parent": "a" },
+                        "c": { "message": message("assistant", "Because"), "parent": "a" },
+                    }
+                },
+                { "title": "Empty", "current_node": null, "mapping": {} },
+            ])
+            .to_string(),
+        )
+        .unwrap();
+
+        let report = import_file(&source, None, temp_dir.path()).unwrap();
+        let expected_path = temp_dir.path().join("conversations/borrow-checker.md");
+        assert_eq!(report.files, vec![expected_path.clone()]);
+        assert_eq!(
+            report.skipped,
+            BTreeMap::from([("conversation without messages".to_string(), 1)])
+        );
+
+        let (front_matter, content) = storage_for_test(&expected_path);
+        assert_eq!(front_matter.created, Some("2024-05-01T12:00:00Z".to_string()));
+        assert_eq!(
+            HistoryFile::parse_messages(&content),
+            vec![("user", "Why?".to_string()), ("assistant", "Because".to_string())]
+        );
+        assert_eq!(
+            HistoryFile::parse_messages_with_metadata(&content)[0].2,
+            Some(MessageMetadata {
+                time: Some("2024-05-01T12:01:00Z".to_string()),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_import_claude_export() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("claude.json");
+        fs::write(
+            &source,
+            serde_json::json!([{
+                "name": "",
+                "created_at": "2024-05-01T12:00:00.000000Z",
+                "chat_messages": [
+                    { "sender": "human", "text": "Hello" },
+                    { "sender": "assistant", "text": "", "content": [{ "type": "text", "text": "Hi" }] },
+                ]
+            }])
+            .to_string(),
+        )
+        .unwrap();
+
+        let report = import_file(&source, Some(ImportFormat::Claude), temp_dir.path()).unwrap();
+        assert_eq!(report.files, vec![temp_dir.path().join("claude.md")]);
+        assert!(report.skipped.is_empty());
+
+        let (front_matter, content) = storage_for_test(&report.files[0]);
+        assert_eq!(front_matter.created, Some("2024-05-01T12:00:00.000000Z".to_string()));
+        assert_eq!(
+            HistoryFile::parse_messages(&content),
+            vec![("user", "Hello".to_string()), ("assistant", "Hi".to_string())]
+        );
+
+        // Importing again does not overwrite the earlier import
+        let report = import_file(&source, None, temp_dir.path()).unwrap();
+        assert_eq!(report.files, vec![temp_dir.path().join("claude-2.md")]);
+    }
+
+    #[test]
+    fn test_import_invalid_source() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("broken.json");
+
+        fs::write(&source, "{ not json").unwrap();
+        assert!(import_file(&source, None, temp_dir.path()).is_err());
+
+        fs::write(&source, r#"{ "unrelated": true }"#).unwrap();
+        assert!(import_file(&source, None, temp_dir.path()).is_err());
+    }
+
+    fn storage_for_test(path: &Path) -> (FrontMatter, String) {
+        let (front_matter, content) = crate::history_file::storage::storage_for(path).read(path).unwrap();
+        (front_matter.unwrap(), content)
+    }
+}
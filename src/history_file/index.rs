@@ -0,0 +1,108 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+/// Index file in the data directory
+pub(crate) const INDEX_FILE: &str = "cforge.index.toml";
+
+/// Generated titles and summaries of chats, so that `:list`, `:search` and completion don't need to read every chat
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq)]
+pub(crate) struct ChatIndex {
+    /// Keyed by the canonical path of the history file
+    #[serde(default)]
+    chats: BTreeMap<String, IndexEntry>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub(crate) struct IndexEntry {
+    pub(crate) title: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) summary: Option<String>,
+}
+
+impl ChatIndex {
+    /// Load the index of a data directory, a missing or invalid index is empty
+    pub(crate) fn load(dir: &Path) -> ChatIndex {
+        let Ok(index_string) = fs::read_to_string(dir.join(INDEX_FILE)) else {
+            return ChatIndex::default();
+        };
+
+        toml::from_str(&index_string).unwrap_or_else(|e| {
+            eprintln!("Failed to parse chat index: {e}");
+            ChatIndex::default()
+        })
+    }
+
+    pub(crate) fn save(&self, dir: &Path) -> io::Result<()> {
+        let index_string = toml::to_string(self).map_err(io::Error::other)?;
+        fs::write(dir.join(INDEX_FILE), index_string)
+    }
+
+    pub(crate) fn get(&self, path: &Path) -> Option<&IndexEntry> {
+        self.chats.get(&Self::key(path))
+    }
+
+    pub(crate) fn set(&mut self, path: &Path, entry: IndexEntry) {
+        self.chats.insert(Self::key(path), entry);
+    }
+
+    fn key(path: &Path) -> String {
+        fs::canonicalize(path)
+            .unwrap_or_else(|_| PathBuf::from(path))
+            .display()
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let chat = temp_dir.path().join("chat.md");
+        fs::write(&chat, "Content").unwrap();
+
+        let mut index = ChatIndex::load(temp_dir.path());
+        assert_eq!(index, ChatIndex::default());
+
+        let entry = IndexEntry {
+            title: "Lifetimes in Rust".to_string(),
+            summary: Some("How lifetimes relate to borrows".to_string()),
+        };
+        index.set(&chat, entry.clone());
+        index.save(temp_dir.path()).unwrap();
+
+        let index = ChatIndex::load(temp_dir.path());
+        assert_eq!(index.get(&chat), Some(&entry));
+        // Relative segments resolve to the same chat
+        assert_eq!(index.get(&temp_dir.path().join(".").join("chat.md")), Some(&entry));
+        assert_eq!(index.get(&temp_dir.path().join("other.md")), None);
+    }
+
+    #[test]
+    fn test_load_invalid_index() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::write(temp_dir.path().join(INDEX_FILE), "not [valid").unwrap();
+        assert_eq!(ChatIndex::load(temp_dir.path()), ChatIndex::default());
+    }
+}
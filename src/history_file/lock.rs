@@ -0,0 +1,101 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// Advisory lock file that marks a history file as open in a cforge session.
+///
+/// The lock file (`<history file>.lock`) contains the process id of the session.
+/// It is removed when the history file is closed.
+#[derive(Debug)]
+pub(crate) struct HistoryLock {
+    path: PathBuf,
+}
+
+impl HistoryLock {
+    /// Take the lock for a history file, warning if another running session holds it
+    pub(crate) fn acquire(history_path: &Path) -> HistoryLock {
+        let path = PathBuf::from(format!("{}.lock", history_path.display()));
+
+        if let Some(pid) = Self::read_pid(&path)
+            && pid != process::id()
+            && Self::is_running(pid)
+        {
+            eprintln!(
+                "Warning: {} is open in another cforge session (pid {pid}), changes from both sessions may be interleaved",
+                history_path.display()
+            );
+        }
+
+        if let Err(e) = fs::write(&path, process::id().to_string()) {
+            eprintln!("Error creating lock file: {e}");
+        }
+
+        HistoryLock { path }
+    }
+
+    fn read_pid(path: &Path) -> Option<u32> {
+        fs::read_to_string(path).ok()?.trim().parse().ok()
+    }
+
+    /// Without `/proc` (e.g. macOS or Windows) the lock is assumed to be held
+    fn is_running(pid: u32) -> bool {
+        let proc_dir = Path::new("/proc");
+        !proc_dir.is_dir() || proc_dir.join(pid.to_string()).exists()
+    }
+}
+
+impl Drop for HistoryLock {
+    fn drop(&mut self) {
+        // Another session may have taken over the lock
+        if Self::read_pid(&self.path) == Some(process::id()) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_is_released() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let history_path = temp_dir.path().join("chat.txt");
+        let lock_path = temp_dir.path().join("chat.txt.lock");
+
+        let lock = HistoryLock::acquire(&history_path);
+        assert_eq!(fs::read_to_string(&lock_path).unwrap(), process::id().to_string());
+
+        drop(lock);
+        assert!(!lock_path.exists());
+    }
+
+    #[test]
+    fn test_lock_taken_over_by_another_session() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let history_path = temp_dir.path().join("chat.txt");
+        let lock_path = temp_dir.path().join("chat.txt.lock");
+
+        let lock = HistoryLock::acquire(&history_path);
+        fs::write(&lock_path, "1").unwrap();
+
+        drop(lock);
+        assert_eq!(fs::read_to_string(&lock_path).unwrap(), "1");
+    }
+}
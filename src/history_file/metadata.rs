@@ -0,0 +1,199 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Optional metadata of a message, stored on its own line inside the message's delimiter.
+/// The metadata is never sent to the model.
+///
+/// ```text
+/// time: 2025-06-01T12:00:00Z; profile: local; model: gemma3:12b; input_tokens: 812; output_tokens: 164
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct MessageMetadata {
+    /// RFC 3339 UTC time the message was sent or received
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) profile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) model: Option<String>,
+    /// Tokens the provider counted for the request, including the history and context
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) input_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) output_tokens: Option<u64>,
+}
+
+impl MessageMetadata {
+    /// Metadata with only the current time
+    pub(crate) fn now() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or_default();
+
+        MessageMetadata {
+            time: Some(format_unix_timestamp(seconds)),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        *self == MessageMetadata::default()
+    }
+
+    /// Parse a metadata line, unknown keys are ignored so that newer files can still be read
+    pub(crate) fn parse(line: &str) -> Result<Self, String> {
+        let mut metadata = MessageMetadata::default();
+
+        for field in line.split(';').map(str::trim).filter(|field| !field.is_empty()) {
+            let (key, value) = field
+                .split_once(':')
+                .ok_or_else(|| format!("invalid metadata field '{field}'"))?;
+            let value = value.trim().to_string();
+            let tokens = |value: &str| {
+                value
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid token count '{value}'"))
+            };
+
+            match key.trim() {
+                "time" => metadata.time = Some(value),
+                "profile" => metadata.profile = Some(value),
+                "model" => metadata.model = Some(value),
+                "input_tokens" => metadata.input_tokens = Some(tokens(&value)?),
+                "output_tokens" => metadata.output_tokens = Some(tokens(&value)?),
+                _ => {}
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    /// Render the metadata line, without a line break
+    pub(crate) fn to_line(&self) -> String {
+        // Separators and line breaks in values would break the line apart
+        let clean = |value: &str| value.replace([';', '\n', '\r'], " ").trim().to_string();
+
+        let mut fields = Vec::new();
+        if let Some(time) = &self.time {
+            fields.push(format!("time: {}", clean(time)));
+        }
+        if let Some(profile) = &self.profile {
+            fields.push(format!("profile: {}", clean(profile)));
+        }
+        if let Some(model) = &self.model {
+            fields.push(format!("model: {}", clean(model)));
+        }
+        if let Some(input_tokens) = self.input_tokens {
+            fields.push(format!("input_tokens: {input_tokens}"));
+        }
+        if let Some(output_tokens) = self.output_tokens {
+            fields.push(format!("output_tokens: {output_tokens}"));
+        }
+
+        fields.join("; ")
+    }
+
+    /// Short description for display, e.g. `2025-06-01 12:00:00 UTC, gemma3:12b (local), 812 in / 164 out tokens`
+    pub(crate) fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(time) = &self.time {
+            parts.push(time.replacen('T', " ", 1).replace('Z', " UTC"));
+        }
+        match (&self.model, &self.profile) {
+            (Some(model), Some(profile)) => parts.push(format!("{model} ({profile})")),
+            (Some(model), None) => parts.push(model.clone()),
+            (None, Some(profile)) => parts.push(profile.clone()),
+            (None, None) => {}
+        }
+        match (self.input_tokens, self.output_tokens) {
+            (Some(input), Some(output)) => parts.push(format!("{input} in / {output} out tokens")),
+            (Some(input), None) => parts.push(format!("{input} in tokens")),
+            (None, Some(output)) => parts.push(format!("{output} out tokens")),
+            (None, None) => {}
+        }
+
+        parts.join(", ")
+    }
+}
+
+/// Format a Unix timestamp as an RFC 3339 UTC date and time
+pub(crate) fn format_unix_timestamp(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86_400);
+    let seconds = timestamp.rem_euclid(86_400);
+
+    // Civil date from days since the epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_line() {
+        let metadata = MessageMetadata {
+            time: Some("2025-06-01T12:00:00Z".to_string()),
+            profile: Some("local".to_string()),
+            model: Some("gemma3:12b".to_string()),
+            input_tokens: Some(812),
+            output_tokens: Some(164),
+        };
+        let line = metadata.to_line();
+
+        assert_eq!(
+            line,
+            "time: 2025-06-01T12:00:00Z; profile: local; model: gemma3:12b; input_tokens: 812; output_tokens: 164"
+        );
+        assert_eq!(MessageMetadata::parse(&line), Ok(metadata.clone()));
+        assert_eq!(
+            metadata.describe(),
+            "2025-06-01 12:00:00 UTC, gemma3:12b (local), 812 in / 164 out tokens"
+        );
+
+        assert_eq!(
+            MessageMetadata::parse("model: a; future: value").unwrap().model,
+            Some("a".to_string())
+        );
+        assert!(MessageMetadata::parse("input_tokens: many").is_err());
+        assert!(MessageMetadata::parse("no fields").is_err());
+    }
+
+    #[test]
+    fn test_format_unix_timestamp() {
+        assert_eq!(format_unix_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_unix_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_unix_timestamp(1_714_564_800), "2024-05-01T12:00:00Z");
+    }
+}
@@ -0,0 +1,306 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use super::storage::storage_for;
+use super::{history_files, ChatIndex, HistoryFile};
+use colored::Colorize;
+use regex::{Regex, RegexBuilder};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Characters of context shown on each side of the first match
+const SNIPPET_CONTEXT: usize = 40;
+
+/// A parsed search query, every term must match a message.
+///
+/// - `word` - case-insensitive word
+/// - `"exact phrase"` - case-insensitive phrase
+/// - `/regex/` - regular expression, the whole query
+/// - `role:<user|assistant|tool|summary>` - only search messages with the role
+#[derive(Debug)]
+pub(crate) struct SearchQuery {
+    terms: Vec<Regex>,
+    role: Option<String>,
+}
+
+/// A message that matches a search query
+#[derive(Debug, PartialEq)]
+pub(crate) struct SearchHit {
+    pub(crate) path: PathBuf,
+    /// 1-based message number in the chat
+    pub(crate) message_number: usize,
+    pub(crate) role: &'static str,
+    /// Title of the chat from the front matter or the chat index
+    pub(crate) title: Option<String>,
+    /// Single-line excerpt around the first match, with matches highlighted
+    pub(crate) snippet: String,
+}
+
+impl SearchQuery {
+    pub(crate) fn parse(query: &str) -> Result<SearchQuery, String> {
+        let mut role = None;
+        let mut rest = Vec::new();
+        for word in query.split_whitespace() {
+            match word.strip_prefix("role:") {
+                Some(value) => match value.to_lowercase().as_str() {
+                    value @ ("user" | "assistant" | "tool" | "summary") => role = Some(value.to_string()),
+                    _ => return Err(format!("Invalid role: {value}, expected user, assistant, tool or summary")),
+                },
+                None => rest.push(word),
+            }
+        }
+        let query = rest.join(" ");
+
+        let patterns: Vec<String> = if let Some(regex) = query
+            .strip_prefix('/')
+            .and_then(|query| query.strip_suffix('/'))
+            .filter(|regex| !regex.is_empty())
+        {
+            vec![regex.to_string()]
+        } else {
+            query
+                .split('"')
+                .enumerate()
+                .flat_map(|(i, part)| {
+                    if i % 2 == 1 {
+                        // Quoted phrases match across any whitespace
+                        let words: Vec<String> = part.split_whitespace().map(regex::escape).collect();
+                        vec![words.join(r"\s+")]
+                    } else {
+                        part.split_whitespace().map(regex::escape).collect()
+                    }
+                })
+                .filter(|pattern| !pattern.is_empty())
+                .collect()
+        };
+
+        if patterns.is_empty() {
+            return Err("Empty search query".to_string());
+        }
+
+        let terms = patterns
+            .iter()
+            .map(|pattern| {
+                RegexBuilder::new(pattern)
+                    .case_insensitive(!query.starts_with('/'))
+                    .build()
+                    .map_err(|e| format!("Invalid regex: {e}"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(SearchQuery { terms, role })
+    }
+
+    fn matches(&self, role: &str, message: &str) -> bool {
+        self.role.as_deref().is_none_or(|r| r == role)
+            && self.terms.iter().all(|term| term.is_match(message))
+    }
+
+    /// Excerpt of the message around the first match of the first term
+    fn snippet(&self, message: &str) -> String {
+        let (first_start, first_end) = self.terms[0]
+            .find(message)
+            .map(|m| (m.start(), m.end()))
+            .unwrap_or_default();
+
+        let start = floor_char_boundary(message, first_start.saturating_sub(SNIPPET_CONTEXT));
+        let end = floor_char_boundary(message, (first_end + SNIPPET_CONTEXT).min(message.len()));
+        let excerpt = message[start..end].split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let mut snippet = String::new();
+        if start > 0 {
+            snippet.push_str("...");
+        }
+        snippet.push_str(&self.highlight(&excerpt));
+        if end < message.len() {
+            snippet.push_str("...");
+        }
+        snippet
+    }
+
+    fn highlight(&self, text: &str) -> String {
+        let mut ranges: Vec<(usize, usize)> = self
+            .terms
+            .iter()
+            .flat_map(|term| term.find_iter(text).map(|m| (m.start(), m.end())))
+            .filter(|(start, end)| start < end)
+            .collect();
+        ranges.sort();
+
+        let mut highlighted = String::new();
+        let mut last_end = 0;
+        for (start, end) in ranges {
+            // Overlapping matches are highlighted once
+            if start < last_end {
+                continue;
+            }
+            highlighted.push_str(&text[last_end..start]);
+            highlighted.push_str(&text[start..end].yellow().bold().to_string());
+            last_end = end;
+        }
+        highlighted.push_str(&text[last_end..]);
+        highlighted
+    }
+}
+
+impl SearchHit {
+    /// Result line with the path relative to `dir`, e.g. `#1 rust/chat.md:3 (Lifetimes) assistant: ...`
+    pub(crate) fn display(&self, number: usize, dir: &Path) -> String {
+        let path = self.path.strip_prefix(dir).unwrap_or(&self.path);
+        let title = self
+            .title
+            .as_ref()
+            .map(|title| format!(" ({title})"))
+            .unwrap_or_default();
+        format!(
+            "{} {}:{}{title} {}: {}",
+            format!("#{number}").bold(),
+            path.display(),
+            self.message_number,
+            self.role,
+            self.snippet
+        )
+    }
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Search the messages of every history file under a directory
+pub(crate) fn search_files(dir: &Path, query: &SearchQuery) -> io::Result<Vec<SearchHit>> {
+    let mut hits = Vec::new();
+    let index = ChatIndex::load(dir);
+
+    for path in history_files(dir)? {
+        // Files that can't be read as chats, e.g. binary files, are not searched
+        let Ok((front_matter, content)) = storage_for(&path).read(&path) else {
+            continue;
+        };
+        let title = front_matter
+            .and_then(|front_matter| front_matter.title)
+            .or_else(|| index.get(&path).map(|entry| entry.title.clone()));
+
+        for (i, (role, message)) in HistoryFile::parse_messages(&content).into_iter().enumerate() {
+            if query.matches(role, &message) {
+                hits.push(SearchHit {
+                    path: path.clone(),
+                    message_number: i + 1,
+                    role,
+                    title: title.clone(),
+                    snippet: query.snippet(&message),
+                });
+            }
+        }
+    }
+
+    Ok(hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history_file::IndexEntry;
+    use std::fs;
+
+    fn chat(messages: &[(&str, &str)]) -> String {
+        messages
+            .iter()
+            .filter_map(|(role, message)| HistoryFile::render_message(role, message, None))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let query = SearchQuery::parse(r#"role:assistant borrow "mutable  reference""#).unwrap();
+        assert_eq!(query.role, Some("assistant".to_string()));
+        assert!(query.matches("assistant", "A Mutable\nreference can't BORROW twice"));
+        assert!(!query.matches("assistant", "A mutable borrow"));
+        assert!(!query.matches("user", "A mutable reference can't borrow twice"));
+
+        let query = SearchQuery::parse("/fn [a-z]+\\(/").unwrap();
+        assert!(query.matches("user", "fn main() {}"));
+        assert!(!query.matches("user", "FN MAIN()"));
+
+        assert!(SearchQuery::parse("role:system test").is_err());
+        assert!(SearchQuery::parse("role:user").is_err());
+        assert!(SearchQuery::parse("/(/").is_err());
+    }
+
+    #[test]
+    fn test_snippet() {
+        colored::control::set_override(false);
+        let query = SearchQuery::parse("needle").unwrap();
+        let message = format!("{}needle\nin the {}", "a".repeat(60), "b".repeat(60));
+
+        assert_eq!(
+            query.snippet(&message),
+            format!("...{}needle in the {}...", "a".repeat(40), "b".repeat(32))
+        );
+        assert_eq!(query.snippet("Short needle"), "Short needle");
+    }
+
+    #[test]
+    fn test_search_files() {
+        colored::control::set_override(false);
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::create_dir(temp_dir.path().join("nested")).unwrap();
+        fs::write(
+            temp_dir.path().join("a.md"),
+            chat(&[("user", "How do lifetimes work?"), ("assistant", "Lifetimes are regions")]),
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("nested/b.md"),
+            chat(&[("user", "Unrelated"), ("assistant", "Still unrelated"), ("user", "lifetimes again")]),
+        )
+        .unwrap();
+        let mut index = ChatIndex::default();
+        index.set(
+            &temp_dir.path().join("nested/b.md"),
+            IndexEntry { title: "Mixed topics".to_string(), summary: None },
+        );
+        index.save(temp_dir.path()).unwrap();
+
+        let hits = search_files(temp_dir.path(), &SearchQuery::parse("lifetimes role:user").unwrap()).unwrap();
+        assert_eq!(
+            hits,
+            vec![
+                SearchHit {
+                    path: temp_dir.path().join("a.md"),
+                    message_number: 1,
+                    role: "user",
+                    title: None,
+                    snippet: "How do lifetimes work?".to_string(),
+                },
+                SearchHit {
+                    path: temp_dir.path().join("nested/b.md"),
+                    message_number: 3,
+                    role: "user",
+                    title: Some("Mixed topics".to_string()),
+                    snippet: "lifetimes again".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            hits[1].display(2, temp_dir.path()),
+            "#2 nested/b.md:3 (Mixed topics) user: lifetimes again"
+        );
+    }
+}
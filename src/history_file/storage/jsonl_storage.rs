@@ -0,0 +1,192 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::history_file::storage::HistoryStorage;
+use crate::history_file::{FrontMatter, HistoryFile, MessageMetadata, FORMAT_VERSION};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// JSON lines, an optional front matter record followed by one record per message.
+///
+/// ```text
+/// {"front_matter":{"version":2,"profile":"local"}}
+/// {"role":"user","content":"Hello"}
+/// {"role":"assistant","content":"Hi!","metadata":{"time":"2025-06-01T12:00:00Z","model":"gemma3:12b"}}
+/// ```
+#[derive(Debug)]
+pub(crate) struct JsonlStorage;
+
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum Record {
+    FrontMatter { front_matter: Box<FrontMatter> },
+    Message {
+        role: String,
+        content: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        metadata: Option<MessageMetadata>,
+    },
+}
+
+impl JsonlStorage {
+    /// Parse a single record, messages are rendered in the text format
+    fn parse_line(line: &str) -> Result<(Option<FrontMatter>, String), String> {
+        match serde_json::from_str::<Record>(line).map_err(|e| e.to_string())? {
+            Record::FrontMatter { front_matter } => Ok((Some(*front_matter), String::new())),
+            Record::Message {
+                role,
+                content,
+                metadata,
+            } => HistoryFile::render_message(&role, &content, metadata.as_ref())
+                .map(|entry| (None, entry))
+                .ok_or_else(|| format!("unknown role '{role}'")),
+        }
+    }
+
+    /// Describe why a line is not a valid record, `None` for valid and blank lines
+    pub(crate) fn line_error(line: &str) -> Option<String> {
+        if line.trim().is_empty() {
+            return None;
+        }
+        Self::parse_line(line).err()
+    }
+
+    fn message_lines(content: &str) -> io::Result<String> {
+        let mut lines = String::new();
+        for (role, content, metadata) in HistoryFile::parse_messages_with_metadata(content) {
+            let record = Record::Message {
+                role: role.to_string(),
+                content,
+                metadata,
+            };
+            lines.push_str(&serde_json::to_string(&record)?);
+            lines.push('\n');
+        }
+        Ok(lines)
+    }
+}
+
+impl HistoryStorage for JsonlStorage {
+    fn read(&self, path: &Path) -> io::Result<(Option<FrontMatter>, String)> {
+        let raw_content = fs::read_to_string(path)?;
+        let mut front_matter = FrontMatter::default();
+        let mut content = String::new();
+
+        for (index, line) in raw_content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (parsed, entry) = Self::parse_line(line).map_err(|message| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}:{}: {message}", path.display(), index + 1),
+                )
+            })?;
+
+            if let Some(parsed) = parsed {
+                front_matter = parsed;
+            }
+            content.push_str(&entry);
+        }
+
+        // Messages are escaped when they are rendered, so the content is always in the current format
+        front_matter.version = Some(FORMAT_VERSION);
+        Ok((Some(front_matter), content))
+    }
+
+    fn write(&self, path: &Path, front_matter: Option<&FrontMatter>, content: &str) -> io::Result<()> {
+        let mut lines = String::new();
+        if let Some(front_matter) = front_matter {
+            let record = Record::FrontMatter {
+                front_matter: Box::new(front_matter.clone()),
+            };
+            lines.push_str(&serde_json::to_string(&record)?);
+            lines.push('\n');
+        }
+        lines.push_str(&Self::message_lines(content)?);
+
+        fs::write(path, lines)
+    }
+
+    fn append(&self, path: &Path, content: &str) -> io::Result<()> {
+        let mut file = OpenOptions::new().append(true).open(path)?;
+        file.write_all(Self::message_lines(content)?.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history_file::DELIMITER_USER_INPUT;
+
+    #[test]
+    fn test_jsonl_storage_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("chat.jsonl");
+        let front_matter = FrontMatter {
+            version: Some(FORMAT_VERSION),
+            profile: Some("local".to_string()),
+            ..Default::default()
+        };
+        let metadata = MessageMetadata {
+            model: Some("gemma3:12b".to_string()),
+            output_tokens: Some(2),
+            ..Default::default()
+        };
+        let content = format!(
+            "{}Hello{}",
+            DELIMITER_USER_INPUT,
+            HistoryFile::render_message("assistant", "Hi!", Some(&metadata)).unwrap()
+        );
+
+        JsonlStorage.write(&path, Some(&front_matter), &content).unwrap();
+        JsonlStorage
+            .append(&path, &format!("{}Multi\nline", DELIMITER_USER_INPUT))
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            concat!(
+                "{\"front_matter\":{\"version\":3,\"profile\":\"local\"}}\n",
+                "{\"role\":\"user\",\"content\":\"Hello\"}\n",
+                "{\"role\":\"assistant\",\"content\":\"Hi!\",\"metadata\":{\"model\":\"gemma3:12b\",\"output_tokens\":2}}\n",
+                "{\"role\":\"user\",\"content\":\"Multi\\nline\"}\n",
+            )
+        );
+        assert_eq!(
+            JsonlStorage.read(&path).unwrap(),
+            (
+                Some(front_matter),
+                format!("{content}{}Multi\nline", DELIMITER_USER_INPUT)
+            )
+        );
+    }
+
+    #[test]
+    fn test_jsonl_storage_invalid_line() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("chat.jsonl");
+        fs::write(&path, "{\"role\":\"user\",\"content\":\"Hello\"}\n{\"role\":\"robot\",\"content\":\"Beep\"}\n").unwrap();
+
+        let error = JsonlStorage.read(&path).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().ends_with("chat.jsonl:2: unknown role 'robot'"));
+    }
+}
@@ -0,0 +1,70 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::history_file::storage::HistoryStorage;
+use crate::history_file::FrontMatter;
+use std::cell::RefCell;
+use std::io;
+use std::path::Path;
+
+/// Keeps a temporary chat in memory, nothing is written to disk
+#[derive(Debug, Default)]
+pub(crate) struct MemoryStorage {
+    chat: RefCell<(Option<FrontMatter>, String)>,
+}
+
+impl HistoryStorage for MemoryStorage {
+    fn read(&self, _path: &Path) -> io::Result<(Option<FrontMatter>, String)> {
+        Ok(self.chat.borrow().clone())
+    }
+
+    fn write(&self, _path: &Path, front_matter: Option<&FrontMatter>, content: &str) -> io::Result<()> {
+        *self.chat.borrow_mut() = (front_matter.cloned(), content.to_string());
+        Ok(())
+    }
+
+    fn append(&self, _path: &Path, content: &str) -> io::Result<()> {
+        self.chat.borrow_mut().1.push_str(content);
+        Ok(())
+    }
+
+    fn is_persistent(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_storage_round_trip() {
+        let storage = MemoryStorage::default();
+        let front_matter = FrontMatter {
+            profile: Some("local".to_string()),
+            ..Default::default()
+        };
+
+        storage.write(Path::new(""), Some(&front_matter), "Content").unwrap();
+        storage.append(Path::new(""), " and more").unwrap();
+
+        assert_eq!(
+            storage.read(Path::new("")).unwrap(),
+            (Some(front_matter), "Content and more".to_string())
+        );
+        assert!(!storage.is_persistent());
+    }
+}
@@ -0,0 +1,68 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+/* Storage backends are implemented one per file, mirroring the api client structure. */
+pub(crate) mod jsonl_storage;
+pub(crate) mod memory_storage;
+pub(crate) mod text_storage;
+
+use crate::history_file::FrontMatter;
+use jsonl_storage::JsonlStorage;
+use std::fmt::Debug;
+use std::io;
+use std::path::Path;
+use text_storage::TextStorage;
+
+/// On-disk format of a history file.
+///
+/// The chat content is always exchanged in the text format, with delimiters and escaped messages,
+/// so that `HistoryFile` works the same way regardless of the backend.
+pub(crate) trait HistoryStorage: Debug {
+    /// Read the front matter and the chat content
+    fn read(&self, path: &Path) -> io::Result<(Option<FrontMatter>, String)>;
+
+    /// Replace the file with the given front matter and chat content
+    fn write(&self, path: &Path, front_matter: Option<&FrontMatter>, content: &str) -> io::Result<()>;
+
+    /// Append chat content, starting with a delimiter, to the file
+    fn append(&self, path: &Path, content: &str) -> io::Result<()>;
+
+    /// Whether the chat is stored on disk, temporary chats are only kept in memory
+    fn is_persistent(&self) -> bool {
+        true
+    }
+}
+
+/// Choose the storage backend by file extension, `.jsonl` files use JSON lines and everything else the text format
+pub(crate) fn storage_for(path: &Path) -> Box<dyn HistoryStorage> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("jsonl") => Box::new(JsonlStorage),
+        _ => Box::new(TextStorage),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_for() {
+        assert_eq!(format!("{:?}", storage_for(Path::new("chat.jsonl"))), "JsonlStorage");
+        assert_eq!(format!("{:?}", storage_for(Path::new("chat.JSONL"))), "JsonlStorage");
+        assert_eq!(format!("{:?}", storage_for(Path::new("chat.txt"))), "TextStorage");
+        assert_eq!(format!("{:?}", storage_for(Path::new("chat"))), "TextStorage");
+    }
+}
@@ -0,0 +1,73 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use crate::history_file::storage::HistoryStorage;
+use crate::history_file::FrontMatter;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// The default format, an optional front matter followed by delimited messages
+#[derive(Debug)]
+pub(crate) struct TextStorage;
+
+impl HistoryStorage for TextStorage {
+    fn read(&self, path: &Path) -> io::Result<(Option<FrontMatter>, String)> {
+        let raw_content = fs::read_to_string(path)?;
+        let (front_matter, content) = FrontMatter::split(&raw_content);
+        Ok((front_matter, content.to_string()))
+    }
+
+    fn write(&self, path: &Path, front_matter: Option<&FrontMatter>, content: &str) -> io::Result<()> {
+        let header = front_matter
+            .map(|front_matter| front_matter.render())
+            .unwrap_or_default();
+
+        fs::write(path, format!("{header}{content}"))
+    }
+
+    fn append(&self, path: &Path, content: &str) -> io::Result<()> {
+        let mut file = OpenOptions::new().append(true).open(path)?;
+        file.write_all(content.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_storage_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("chat.txt");
+        let front_matter = FrontMatter {
+            profile: Some("local".to_string()),
+            ..Default::default()
+        };
+
+        TextStorage.write(&path, Some(&front_matter), "Content").unwrap();
+        TextStorage.append(&path, " and more").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "+++\nprofile = \"local\"\n+++\nContent and more"
+        );
+        assert_eq!(
+            TextStorage.read(&path).unwrap(),
+            (Some(front_matter), "Content and more".to_string())
+        );
+    }
+}
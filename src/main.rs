@@ -17,7 +17,9 @@
 pub mod api;
 mod command;
 pub mod config;
+mod context;
 mod history_file;
+mod render;
 pub mod tool;
 mod user_input;
 
@@ -27,52 +29,126 @@ mod test_support;
 use crate::api::{get_chat_client_implementation, ChatClient};
 use crate::command::commands::{create_command_registry, CommandResult};
 use crate::config::AppConfig;
-use crate::history_file::HistoryFile;
-use clap::Parser;
+use crate::context::{CommandLimits, ContextEntry, ContextSet, LoadedContext};
+use crate::history_file::{ExportFormat, HistoryFile, ImportFormat, SearchQuery};
+use clap::{Parser, Subcommand};
 use colored::Colorize;
 use command::processor::CommandProcessor;
-use std::fs::{self};
 use std::io::{self};
 use std::path::PathBuf;
 
 #[derive(Parser)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Args {
     /// Path to file containing chat history. Can be either relative (to `cforge_dir`) or absolute.
     /// If not provided, the last history file will be used, which is saved in `~/.cforge.toml`.
     history_file: Option<String>,
 
-    /// Optional file with content to be used as input for each chat message
-    #[arg(short = 'f', long = "file")]
-    context_file: Option<PathBuf>,
+    /// File, directory or glob with content to be used as input for each chat message.
+    /// Can be given multiple times, directories are read recursively respecting `.gitignore`.
+    /// Prefix with `once:` to send it only with the next message or `diff:` to send the changes since it was last sent.
+    #[arg(short = 'f', long = "file", value_name = "PATH")]
+    context_files: Vec<String>,
+
+    /// Shell command whose output is used as context for each chat message. The command is run again for
+    /// each message. Can be given multiple times and prefixed like `--file`.
+    #[arg(short = 'c', long = "context-cmd", value_name = "COMMAND")]
+    context_commands: Vec<String>,
+
+    /// Start a temporary chat that is only kept in memory. Use `:persist` to save it.
+    #[arg(long = "temp", conflicts_with = "history_file")]
+    temp: bool,
+
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+}
+
+#[derive(Subcommand)]
+enum CliCommand {
+    /// Check a history file for ambiguous or corrupt segments
+    Check {
+        /// Path to the history file. Can be either relative (to `cforge_dir`) or absolute.
+        file: String,
+    },
+    /// Export a chat to Markdown, HTML or JSON for sharing
+    Export {
+        /// Path to the history file. Can be either relative (to `cforge_dir`) or absolute.
+        file: String,
+        /// Export format: md, html or json
+        format: String,
+        /// Destination file, the export is printed if not provided
+        destination: Option<PathBuf>,
+    },
+    /// Import conversations from other tools' JSON exports as history files in the data directory
+    Import {
+        /// Export file to import
+        file: PathBuf,
+        /// Export format: messages (Ollama/OpenAI style), chatgpt or claude. Detected if not provided.
+        #[arg(long)]
+        format: Option<String>,
+    },
+    /// Search the messages of all chats in the data directory
+    Search {
+        /// Words, a "quoted phrase" or a /regex/, optionally with role:<user|assistant|tool|summary>
+        #[arg(required = true, allow_hyphen_values = true)]
+        query: Vec<String>,
+    },
 }
 
 fn main() -> io::Result<()> {
     let mut app_config = AppConfig::load_config();
+    render::set_render_markdown(app_config.user_config.render_markdown);
     let args = Args::parse();
     let command_registry = create_command_registry(app_config.user_config.command_prefixes.clone());
-    let mut context_file_path = args.context_file.clone();
-
-    let history_path = args.history_file.unwrap_or_else(|| {
-        match app_config.cache_config.last_history_file.clone() {
-            Some(path) => path,
-            None => {
-                println!(
-                    "You must specify a history file `cforge <history_file>` for the first time."
-                );
-                println!("See `cforge --help` for more information.");
-                panic!("No history file specified and no previous history file found.");
+    let mut context = ContextSet::new(
+        args.context_files
+            .iter()
+            .map(|path| ContextEntry::parse_path(path))
+            .chain(args.context_commands.iter().map(|command| ContextEntry::parse_command(command))),
+    );
+
+    if let Some(command) = args.command {
+        return run_cli_command(command, &mut app_config);
+    }
+
+    // Temporary chats are not recorded as the last history file
+    let mut history = if args.temp {
+        println!("Starting a temporary chat, use :persist <history file> to save it");
+        HistoryFile::temporary()
+    } else {
+        let history_path = args.history_file.unwrap_or_else(|| {
+            match app_config.cache_config.last_history_file.clone() {
+                Some(path) => path,
+                None => {
+                    println!(
+                        "You must specify a history file `cforge <history_file>` for the first time."
+                    );
+                    println!("See `cforge --help` for more information.");
+                    panic!("No history file specified and no previous history file found.");
+                }
             }
-        }
-    });
+        });
+
+        // `#<number>` refers to a result of the last search
+        let history_path = app_config
+            .resolve_history_file(&history_path)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        app_config.update_last_history_file(history_path.clone());
 
-    app_config.update_last_history_file(history_path.clone());
+        HistoryFile::new(
+            history_path.clone(),
+            app_config.data_dir.display().to_string(),
+        )?
+    };
+    render::print_recent(&history);
+
+    // Restore the session settings stored in the history file, the context file arguments take precedence
+    let front_matter = history.front_matter().cloned().unwrap_or_default();
+    app_config.restore_session(&front_matter);
+    if context.is_empty() {
+        context = ContextSet::from_front_matter(&front_matter);
+    }
 
-    let mut history = HistoryFile::new(
-        history_path.clone(),
-        app_config.data_dir.display().to_string(),
-    )?;
-    println!("{}", history.get_content());
     println!(
         "\n\nYou're conversing with model '{}' ({}) from profile '{}'",
         &app_config.current_model,
@@ -83,19 +159,17 @@ fn main() -> io::Result<()> {
     let mut chat_client: Box<dyn ChatClient> = get_chat_client_implementation(
         &app_config.current_profile.provider,
         &app_config.current_model.model,
-        app_config.user_config.system_prompt.clone(),
+        front_matter
+            .system_prompt
+            .unwrap_or_else(|| app_config.user_config.system_prompt.clone()),
         app_config.user_config.max_tokens,
     );
+    chat_client.update_enabled_tools(front_matter.tools);
     let mut rebuild_chat_client = false;
 
     loop {
         if rebuild_chat_client {
-            chat_client = get_chat_client_implementation(
-                &app_config.current_profile.provider,
-                &app_config.current_model.model,
-                app_config.user_config.system_prompt.clone(),
-                app_config.user_config.max_tokens,
-            );
+            chat_client = api::rebuild_chat_client(chat_client.as_ref(), &app_config);
             rebuild_chat_client = false;
         }
 
@@ -104,27 +178,17 @@ fn main() -> io::Result<()> {
             println!("Model supports tools");
         }
 
-        // Read the context file if provided
-        let context_file_content = if let Some(file_path) = &context_file_path {
-            match fs::read_to_string(file_path.clone()) {
-                Ok(content) => Some(content),
-                Err(e) => {
-                    eprintln!("Error reading context file: {e}");
-                    None
-                }
-            }
-        } else {
-            None
-        };
+        // Read the context files and run the context commands on every prompt to pick up changes
+        let loaded_context = context.load(&CommandLimits::from_config(&app_config.user_config));
 
         if let Some(model_context_size) = chat_client.model_context_size()
             && app_config.user_config.token_estimation
         {
             print_token_usage(
-                estimate_token_count(history.get_content())
-                    + estimate_token_count(context_file_content.as_deref().unwrap_or("")),
+                estimate_token_count(history.get_content()) + loaded_context.tokens(),
                 model_context_size,
             );
+            print_context_tokens(&loaded_context);
         }
 
         println!(
@@ -139,6 +203,9 @@ fn main() -> io::Result<()> {
             }
         };
         let readline = rl.readline(">> ");
+        if let Some((columns, _)) = rl.dimensions() {
+            render::set_terminal_width(columns.into());
+        }
         let user_prompt = match readline {
             Ok(line) => line,
             Err(e) => {
@@ -152,9 +219,9 @@ fn main() -> io::Result<()> {
             &mut history,
             &mut app_config,
             &command_registry,
-            &mut context_file_path,
+            &mut context,
             &mut rebuild_chat_client,
-            context_file_content.clone(),
+            &loaded_context,
         );
 
         match processor.process(&user_prompt) {
@@ -170,6 +237,82 @@ fn main() -> io::Result<()> {
     Ok(())
 }
 
+/// Run a command given on the command line instead of starting a chat
+fn run_cli_command(command: CliCommand, app_config: &mut AppConfig) -> io::Result<()> {
+    match command {
+        CliCommand::Check { file } => {
+            let path = HistoryFile::resolve_path(&file, &app_config.data_dir.display().to_string());
+            let report = history_file::check_file(&path)?;
+
+            match report.version {
+                Some(version) => println!("{}: history format version {version}", path.display()),
+                None => println!(
+                    "{}: original history format, it is migrated when the file is opened",
+                    path.display()
+                ),
+            }
+
+            for issue in &report.issues {
+                println!("{}:{}: {}", path.display(), issue.line, issue.description);
+            }
+
+            if report.issues.is_empty() {
+                println!("No problems found");
+            } else {
+                std::process::exit(1);
+            }
+        }
+        CliCommand::Export { file, format, destination } => {
+            let format = ExportFormat::parse_export_format(&format)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let path = HistoryFile::resolve_path(&file, &app_config.data_dir.display().to_string());
+            let title = path.file_name().unwrap_or_default().to_string_lossy();
+            let content = format.render(&title, &HistoryFile::read_content_json(&path)?)?;
+
+            match destination {
+                Some(destination) => {
+                    history_file::write_export(&destination, &content)?;
+                    println!("Exported {} to {}", path.display(), destination.display());
+                }
+                None => print!("{content}"),
+            }
+        }
+        CliCommand::Import { file, format } => {
+            let format = format
+                .map(|format| ImportFormat::parse_import_format(&format))
+                .transpose()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let report = history_file::import_file(&file, format, &app_config.data_dir)?;
+
+            for path in &report.files {
+                println!("Created {}", path.display());
+            }
+            println!(
+                "Imported {} messages into {} history files",
+                report.message_count,
+                report.files.len()
+            );
+            for (reason, count) in &report.skipped {
+                println!("Skipped {reason}: {count}");
+            }
+        }
+        CliCommand::Search { query } => {
+            let query = SearchQuery::parse(&query.join(" "))
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let hits = history_file::search_files(&app_config.data_dir, &query)?;
+
+            for (i, hit) in hits.iter().enumerate() {
+                println!("{}", hit.display(i + 1, &app_config.data_dir));
+            }
+            println!("Found {} messages, open a chat with cforge '#<number>'", hits.len());
+
+            app_config.update_last_results(hits.into_iter().map(|hit| hit.path.display().to_string()).collect());
+        }
+    }
+
+    Ok(())
+}
+
 /// Calculate and visualize token usage compared to model context size
 fn print_token_usage(estimated_tokens: usize, context_size: usize) {
     let percentage = (estimated_tokens as f64 / context_size as f64 * 100.0).min(100.0);
@@ -198,7 +341,26 @@ fn print_token_usage(estimated_tokens: usize, context_size: usize) {
     println!("\n\nEstimated token usage (1 token ≈ 4 characters): {bar}");
 }
 
-fn estimate_token_count(prompt: &str) -> usize {
+/// Context files and commands listed in the token usage, the rest are summed up
+const CONTEXT_ITEMS_SHOWN: usize = 10;
+
+/// Show the estimated tokens of each context file and command below the usage bar
+fn print_context_tokens(loaded_context: &LoadedContext) {
+    for item in loaded_context.items.iter().take(CONTEXT_ITEMS_SHOWN) {
+        println!("  {} ({})", item.label, item.describe());
+    }
+
+    let rest = &loaded_context.items[loaded_context.items.len().min(CONTEXT_ITEMS_SHOWN)..];
+    if !rest.is_empty() {
+        println!(
+            "  ... and {} more (~{} tokens)",
+            rest.len(),
+            rest.iter().map(|item| item.tokens()).sum::<usize>()
+        );
+    }
+}
+
+pub(crate) fn estimate_token_count(prompt: &str) -> usize {
     let char_count = prompt.chars().count();
     char_count / 4 + 1 // Add 1 to avoid returning 0 for very short content
 }
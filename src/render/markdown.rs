@@ -0,0 +1,587 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use colored::Colorize;
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+lazy_static! {
+    static ref HEADING_REGEX: Regex =
+        Regex::new(r"^\s{0,3}(#{1,6})\s+(.*?)(?:\s+#+)?\s*$").unwrap();
+    static ref RULE_REGEX: Regex =
+        Regex::new(r"^\s{0,3}(?:(?:-\s*){3,}|(?:\*\s*){3,}|(?:_\s*){3,})$").unwrap();
+    static ref QUOTE_REGEX: Regex = Regex::new(r"^\s*>\s?(.*)$").unwrap();
+    static ref UNORDERED_REGEX: Regex = Regex::new(r"^(\s*)[-*+]\s+(.*)$").unwrap();
+    static ref ORDERED_REGEX: Regex = Regex::new(r"^(\s*)(\d+[.)])\s+(.*)$").unwrap();
+    static ref CODE_SPAN_REGEX: Regex = Regex::new(r"`([^`]+)`").unwrap();
+    static ref LINK_REGEX: Regex = Regex::new(r"\[([^\]]+)\]\(([^)\s]+)\)").unwrap();
+    static ref BOLD_REGEX: Regex = Regex::new(r"\*\*([^*]+?)\*\*|__([^_]+?)__").unwrap();
+    static ref ITALIC_REGEX: Regex =
+        Regex::new(r"\*([^*\s](?:[^*]*[^*\s])?)\*|(?:^|\b)_([^_\s](?:[^_]*[^_\s])?)_(?:\b|$)")
+            .unwrap();
+    static ref STRIKETHROUGH_REGEX: Regex = Regex::new(r"~~([^~]+?)~~").unwrap();
+    static ref ANSI_REGEX: Regex = Regex::new(r"\x1b\[[0-9;]*m").unwrap();
+}
+
+/// Render markdown for the terminal, tables are fit to `width` columns
+pub(crate) fn render_markdown(markdown: &str, width: usize) -> String {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut output = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let trimmed = lines[i].trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            let fence = &trimmed[..3];
+            let language = trimmed[3..].trim().to_lowercase();
+            let start = i + 1;
+            i = start;
+            while i < lines.len() && !lines[i].trim_start().starts_with(fence) {
+                i += 1;
+            }
+            output.push(render_code_block(&language, &lines[start..i]));
+            // Skip the closing fence
+            i += 1;
+            continue;
+        }
+
+        if is_table_row(lines[i])
+            && lines
+                .get(i + 1)
+                .is_some_and(|line| is_table_separator(line))
+        {
+            let start = i;
+            i += 2;
+            while i < lines.len() && is_table_row(lines[i]) {
+                i += 1;
+            }
+            output.push(render_table(&lines[start..i], width));
+            continue;
+        }
+
+        output.push(render_line(lines[i], width));
+        i += 1;
+    }
+
+    output.join("\n")
+}
+
+fn render_line(line: &str, width: usize) -> String {
+    if let Some(heading) = HEADING_REGEX.captures(line) {
+        let text = render_inline(&heading[2]);
+        return match heading[1].len() {
+            1 => text.bold().underline().to_string(),
+            _ => text.bold().to_string(),
+        };
+    }
+
+    if RULE_REGEX.is_match(line) {
+        return "─".repeat(width.min(67)).dimmed().to_string();
+    }
+
+    if let Some(quote) = QUOTE_REGEX.captures(line) {
+        return format!("{} {}", "│".dimmed(), render_inline(&quote[1]).italic());
+    }
+
+    if let Some(item) = UNORDERED_REGEX.captures(line) {
+        return format!("{}{} {}", &item[1], "•".bold(), render_inline(&item[2]));
+    }
+
+    if let Some(item) = ORDERED_REGEX.captures(line) {
+        return format!("{}{} {}", &item[1], item[2].bold(), render_inline(&item[3]));
+    }
+
+    render_inline(line)
+}
+
+/// Bold, italic, strikethrough, links and code spans, markdown is not formatted inside code spans
+fn render_inline(text: &str) -> String {
+    let mut rendered = String::new();
+    let mut last_end = 0;
+
+    for code in CODE_SPAN_REGEX.captures_iter(text) {
+        let span = code.get(0).unwrap();
+        rendered.push_str(&render_emphasis(&text[last_end..span.start()]));
+        rendered.push_str(&code[1].cyan().to_string());
+        last_end = span.end();
+    }
+    rendered.push_str(&render_emphasis(&text[last_end..]));
+
+    rendered
+}
+
+fn render_emphasis(text: &str) -> String {
+    let first_group = |captures: &Captures| {
+        captures
+            .iter()
+            .skip(1)
+            .flatten()
+            .next()
+            .map(|group| group.as_str().to_string())
+            .unwrap_or_default()
+    };
+
+    let text = LINK_REGEX.replace_all(text, |link: &Captures| {
+        format!(
+            "{} {}",
+            link[1].underline(),
+            format!("({})", &link[2]).dimmed()
+        )
+    });
+    let text = BOLD_REGEX.replace_all(&text, |bold: &Captures| {
+        first_group(bold).bold().to_string()
+    });
+    let text = ITALIC_REGEX.replace_all(&text, |italic: &Captures| {
+        let whole = italic.get(0).unwrap().as_str();
+        // Keep the word boundary characters matched around underscores
+        let prefix = &whole[..whole.find(['*', '_']).unwrap_or(0)];
+        format!("{prefix}{}", first_group(italic).italic())
+    });
+    let text = STRIKETHROUGH_REGEX.replace_all(&text, |strike: &Captures| {
+        strike[1].strikethrough().to_string()
+    });
+
+    text.into_owned()
+}
+
+/// Width of text in the terminal, without color codes
+fn visible_width(text: &str) -> usize {
+    ANSI_REGEX.replace_all(text, "").chars().count()
+}
+
+fn is_table_row(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with('|') && line.len() > 1
+}
+
+fn is_table_separator(line: &str) -> bool {
+    let cells = table_cells(line);
+    !cells.is_empty()
+        && cells.iter().all(|cell| {
+            let cell = cell.trim_matches(':');
+            !cell.is_empty() && cell.chars().all(|c| c == '-')
+        })
+}
+
+fn table_cells(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+
+    line.replace("\\|", "\u{0}")
+        .split('|')
+        .map(|cell| cell.trim().replace('\u{0}', "|"))
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+/// A table with aligned columns, columns are shortened until the table fits the width
+fn render_table(lines: &[&str], width: usize) -> String {
+    let alignments: Vec<Alignment> = table_cells(lines[1])
+        .iter()
+        .map(|cell| match (cell.starts_with(':'), cell.ends_with(':')) {
+            (true, true) => Alignment::Center,
+            (false, true) => Alignment::Right,
+            _ => Alignment::Left,
+        })
+        .collect();
+
+    let mut rows: Vec<Vec<String>> = std::iter::once(lines[0])
+        .chain(lines[2..].iter().copied())
+        .map(|line| {
+            table_cells(line)
+                .iter()
+                .map(|cell| render_inline(cell))
+                .collect()
+        })
+        .collect();
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    for row in rows.iter_mut() {
+        row.resize(columns, String::new());
+    }
+
+    let mut widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            rows.iter()
+                .map(|row| visible_width(&row[column]))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    // Columns are separated by " │ "
+    let separators = columns.saturating_sub(1) * 3;
+    while widths.iter().sum::<usize>() + separators > width {
+        let Some(widest) = (0..columns).max_by_key(|&column| widths[column]) else {
+            break;
+        };
+        if widths[widest] <= 3 {
+            break;
+        }
+        widths[widest] -= 1;
+    }
+
+    let render_row = |row: &[String], header: bool| {
+        row.iter()
+            .enumerate()
+            .map(|(column, cell)| {
+                let cell = fit_cell(cell, widths[column]);
+                let cell = if header {
+                    cell.bold().to_string()
+                } else {
+                    cell
+                };
+                let padding = widths[column].saturating_sub(visible_width(&cell));
+                match alignments.get(column).copied().unwrap_or(Alignment::Left) {
+                    Alignment::Left => format!("{cell}{}", " ".repeat(padding)),
+                    Alignment::Right => format!("{}{cell}", " ".repeat(padding)),
+                    Alignment::Center => format!(
+                        "{}{cell}{}",
+                        " ".repeat(padding / 2),
+                        " ".repeat(padding - padding / 2)
+                    ),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(&format!(" {} ", "│".dimmed()))
+            .trim_end()
+            .to_string()
+    };
+
+    let mut table = vec![render_row(&rows[0], true)];
+    table.push(
+        widths
+            .iter()
+            .map(|&width| "─".repeat(width))
+            .collect::<Vec<_>>()
+            .join("─┼─")
+            .dimmed()
+            .to_string(),
+    );
+    table.extend(rows[1..].iter().map(|row| render_row(row, false)));
+
+    table.join("\n")
+}
+
+/// Shorten a cell that doesn't fit its column, the formatting of a shortened cell is left out
+fn fit_cell(cell: &str, width: usize) -> String {
+    if visible_width(cell) <= width {
+        return cell.to_string();
+    }
+
+    let plain = ANSI_REGEX.replace_all(cell, "");
+    let shortened: String = plain.chars().take(width.saturating_sub(1)).collect();
+    format!("{shortened}…")
+}
+
+/// Code is printed as is between dimmed fences, so it can be copied from the terminal
+fn render_code_block(language: &str, lines: &[&str]) -> String {
+    let syntax = Syntax::for_language(language);
+    let mut block = vec![format!("```{language}").dimmed().to_string()];
+    block.extend(lines.iter().map(|line| match &syntax {
+        Some(syntax) => highlight_line(line, syntax),
+        None => line.to_string(),
+    }));
+    block.push("```".dimmed().to_string());
+
+    block.join("\n")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TokenKind {
+    Plain,
+    Keyword,
+    String,
+    Number,
+    Comment,
+    /// Keys in JSON objects and TOML tables, and TOML table headers
+    Key,
+}
+
+/// Just enough of a language's syntax to highlight it line by line
+struct Syntax {
+    line_comment: Option<&'static str>,
+    quotes: &'static [char],
+    keywords: &'static [&'static str],
+    /// `key = value` and `[table]` lines
+    toml: bool,
+    /// `"key": value` pairs
+    json: bool,
+}
+
+impl Syntax {
+    fn for_language(language: &str) -> Option<Syntax> {
+        let syntax = match language {
+            "rust" | "rs" => Syntax {
+                line_comment: Some("//"),
+                quotes: &['"'],
+                keywords: &[
+                    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+                    "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop",
+                    "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self",
+                    "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+                    "while",
+                ],
+                toml: false,
+                json: false,
+            },
+            "sh" | "bash" | "shell" | "zsh" | "console" => Syntax {
+                line_comment: Some("#"),
+                quotes: &['"', '\''],
+                keywords: &[
+                    "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done",
+                    "case", "esac", "function", "in", "return", "export", "local", "echo", "cd",
+                    "exit",
+                ],
+                toml: false,
+                json: false,
+            },
+            "toml" => Syntax {
+                line_comment: Some("#"),
+                quotes: &['"', '\''],
+                keywords: &["true", "false"],
+                toml: true,
+                json: false,
+            },
+            "json" => Syntax {
+                line_comment: None,
+                quotes: &['"'],
+                keywords: &["true", "false", "null"],
+                toml: false,
+                json: true,
+            },
+            "python" | "py" => Syntax {
+                line_comment: Some("#"),
+                quotes: &['"', '\''],
+                keywords: &[
+                    "and", "as", "assert", "async", "await", "break", "class", "continue", "def",
+                    "del", "elif", "else", "except", "False", "finally", "for", "from", "global",
+                    "if", "import", "in", "is", "lambda", "None", "nonlocal", "not", "or", "pass",
+                    "raise", "return", "self", "True", "try", "while", "with", "yield",
+                ],
+                toml: false,
+                json: false,
+            },
+            _ => return None,
+        };
+        Some(syntax)
+    }
+}
+
+fn highlight_line(line: &str, syntax: &Syntax) -> String {
+    tokenize(line, syntax)
+        .into_iter()
+        .map(|(kind, text)| match kind {
+            TokenKind::Plain => text,
+            TokenKind::Keyword => text.blue().bold().to_string(),
+            TokenKind::String => text.green().to_string(),
+            TokenKind::Number => text.magenta().to_string(),
+            TokenKind::Comment => text.dimmed().to_string(),
+            TokenKind::Key => text.yellow().to_string(),
+        })
+        .collect()
+}
+
+/// Split a line of code into highlighted tokens, strings and comments don't continue on the next line
+fn tokenize(line: &str, syntax: &Syntax) -> Vec<(TokenKind, String)> {
+    if syntax.toml && line.trim_start().starts_with('[') {
+        return vec![(TokenKind::Key, line.to_string())];
+    }
+
+    let chars: Vec<char> = line.chars().collect();
+    let mut tokens: Vec<(TokenKind, String)> = Vec::new();
+    let mut push = |kind: TokenKind, text: String| match tokens.last_mut() {
+        Some((last_kind, last_text)) if *last_kind == kind && kind == TokenKind::Plain => {
+            last_text.push_str(&text)
+        }
+        _ => tokens.push((kind, text)),
+    };
+    let rest = |i: usize| chars[i..].iter().collect::<String>();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if let Some(comment) = syntax.line_comment
+            && rest(i).starts_with(comment)
+        {
+            push(TokenKind::Comment, rest(i));
+            break;
+        }
+
+        if syntax.quotes.contains(&c) {
+            let start = i;
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(chars.len());
+            let text: String = chars[start..i].iter().collect();
+            let is_key = syntax.json && rest(i).trim_start().starts_with(':');
+            push(
+                if is_key {
+                    TokenKind::Key
+                } else {
+                    TokenKind::String
+                },
+                text,
+            );
+            continue;
+        }
+
+        let follows_word = i > 0 && (chars[i - 1].is_alphanumeric() || chars[i - 1] == '_');
+        if c.is_ascii_digit() && !follows_word {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+            {
+                i += 1;
+            }
+            push(TokenKind::Number, chars[start..i].iter().collect());
+            continue;
+        }
+
+        if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric()
+                    || chars[i] == '_'
+                    || (syntax.toml && chars[i] == '-'))
+            {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            let is_key = syntax.toml
+                && chars[..start].iter().all(|c| c.is_whitespace())
+                && rest(i).trim_start().starts_with('=');
+            let kind = if is_key {
+                TokenKind::Key
+            } else if syntax.keywords.contains(&word.as_str()) {
+                TokenKind::Keyword
+            } else {
+                TokenKind::Plain
+            };
+            push(kind, word);
+            continue;
+        }
+
+        push(TokenKind::Plain, c.to_string());
+        i += 1;
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown() {
+        colored::control::set_override(false);
+        let markdown = "# Title\n\nSome **bold**, *italic* and `**code**` text, see [docs](https://example.com).\n\n- First\n  - Nested\n1. One\n> Quote\n---";
+
+        assert_eq!(
+            render_markdown(markdown, 80),
+            "Title\n\nSome bold, italic and **code** text, see docs (https://example.com).\n\n• First\n  • Nested\n1. One\n│ Quote\n───────────────────────────────────────────────────────────────────"
+        );
+        assert_eq!(
+            render_markdown("snake_case_name and _emphasis_", 80),
+            "snake_case_name and emphasis"
+        );
+        assert_eq!(
+            render_markdown("```rust\nlet x = 1; // one\n```\nAfter", 80),
+            "```rust\nlet x = 1; // one\n```\nAfter"
+        );
+    }
+
+    #[test]
+    fn test_render_table() {
+        colored::control::set_override(false);
+        let table = "| Name | Count |\n|:-----|------:|\n| apples | 3 |\n| pears \\| plums | 12 |";
+
+        assert_eq!(
+            render_markdown(table, 80),
+            "Name          │ Count\n──────────────┼──────\napples        │     3\npears | plums │    12"
+        );
+        assert_eq!(
+            render_markdown(table, 16),
+            "Name     │ Count\n─────────┼──────\napples   │     3\npears |… │    12"
+        );
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let rust = Syntax::for_language("rust").unwrap();
+        assert_eq!(
+            tokenize("let s = \"a\\\"b\"; // done", &rust),
+            vec![
+                (TokenKind::Keyword, "let".to_string()),
+                (TokenKind::Plain, " s = ".to_string()),
+                (TokenKind::String, "\"a\\\"b\"".to_string()),
+                (TokenKind::Plain, "; ".to_string()),
+                (TokenKind::Comment, "// done".to_string()),
+            ]
+        );
+
+        let json = Syntax::for_language("json").unwrap();
+        assert_eq!(
+            tokenize("{\"count\": 12, \"ok\": true}", &json),
+            vec![
+                (TokenKind::Plain, "{".to_string()),
+                (TokenKind::Key, "\"count\"".to_string()),
+                (TokenKind::Plain, ": ".to_string()),
+                (TokenKind::Number, "12".to_string()),
+                (TokenKind::Plain, ", ".to_string()),
+                (TokenKind::Key, "\"ok\"".to_string()),
+                (TokenKind::Plain, ": ".to_string()),
+                (TokenKind::Keyword, "true".to_string()),
+                (TokenKind::Plain, "}".to_string()),
+            ]
+        );
+
+        let toml = Syntax::for_language("toml").unwrap();
+        assert_eq!(
+            tokenize("[profiles]", &toml),
+            vec![(TokenKind::Key, "[profiles]".to_string())]
+        );
+        assert_eq!(
+            tokenize("max-tokens = 1024 # limit", &toml),
+            vec![
+                (TokenKind::Key, "max-tokens".to_string()),
+                (TokenKind::Plain, " = ".to_string()),
+                (TokenKind::Number, "1024".to_string()),
+                (TokenKind::Plain, " ".to_string()),
+                (TokenKind::Comment, "# limit".to_string()),
+            ]
+        );
+
+        let python = Syntax::for_language("py").unwrap();
+        assert_eq!(
+            tokenize("def f2():", &python)[0],
+            (TokenKind::Keyword, "def".to_string())
+        );
+        assert!(Syntax::for_language("shell").is_some());
+        assert!(Syntax::for_language("cobol").is_none());
+    }
+}
@@ -0,0 +1,155 @@
+/*
+ * Copyright © 2025 Mitja Leino
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated
+ * documentation files (the “Software”), to deal in the Software without restriction, including without limitation
+ * the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software,
+ * and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE
+ * WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS
+ * OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+ * TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+mod markdown;
+
+use crate::history_file::{HistoryFile, MessageMetadata};
+use colored::{ColoredString, Colorize};
+use std::env;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Render assistant messages as markdown, see `set_render_markdown`
+static RENDER_MARKDOWN: AtomicBool = AtomicBool::new(false);
+
+/// Columns of the terminal, 0 if not known yet
+static TERMINAL_WIDTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Used when the terminal width is not known and `COLUMNS` is not set
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+
+/// Messages printed when a chat is opened, the rest are left out
+const RECENT_MESSAGES: usize = 20;
+
+/// Width of the separator lines around a message header
+const SEPARATOR_WIDTH: usize = 67;
+
+/// Role labels are indented like in the history file
+const LABEL_INDENT: usize = 24;
+
+/// Render assistant messages as markdown, set from the `render_markdown` config
+pub(crate) fn set_render_markdown(enabled: bool) {
+    RENDER_MARKDOWN.store(enabled, Ordering::Relaxed);
+}
+
+/// Update the terminal width, e.g. from the line editor
+pub(crate) fn set_terminal_width(columns: usize) {
+    TERMINAL_WIDTH.store(columns, Ordering::Relaxed);
+}
+
+fn terminal_width() -> usize {
+    match TERMINAL_WIDTH.load(Ordering::Relaxed) {
+        0 => env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.parse().ok())
+            .filter(|&columns| columns > 0)
+            .unwrap_or(DEFAULT_TERMINAL_WIDTH),
+        columns => columns,
+    }
+}
+
+fn role_label(role: &str) -> &str {
+    match role {
+        "user" => "User Input",
+        "assistant" => "AI Response",
+        "tool" => "Tool Input",
+        "summary" => "Summary",
+        _ => role,
+    }
+}
+
+/// Colors are left out when `NO_COLOR` is set or the output is not a terminal
+fn colorize(role: &str, text: String) -> ColoredString {
+    match role {
+        "assistant" => text.yellow(),
+        "tool" => text.cyan(),
+        "summary" => text.magenta(),
+        _ => text.normal(),
+    }
+}
+
+/// Header of a message, with the role label and a line describing the metadata
+pub(crate) fn render_header(role: &str, metadata: Option<&MessageMetadata>) -> String {
+    let separator = "-".repeat(SEPARATOR_WIDTH);
+    let label = format!("{}--- {} ---", " ".repeat(LABEL_INDENT), role_label(role));
+    let details = metadata
+        .filter(|metadata| !metadata.is_empty())
+        .map(|metadata| format!("{}\n", metadata.describe().dimmed()))
+        .unwrap_or_default();
+
+    format!(
+        "{}{details}{}",
+        colorize(role, format!("\n\n{separator}\n{label}\n")),
+        colorize(role, format!("{separator}\n"))
+    )
+}
+
+/// A message for display in the terminal, the history file keeps the raw markdown
+pub(crate) fn render_message(
+    role: &str,
+    content: &str,
+    metadata: Option<&MessageMetadata>,
+) -> String {
+    let content = if role == "assistant" && RENDER_MARKDOWN.load(Ordering::Relaxed) {
+        markdown::render_markdown(content, terminal_width())
+    } else {
+        content.to_string()
+    };
+
+    format!("{}{content}", render_header(role, metadata))
+}
+
+/// Print the last messages of a chat, long chats are not printed in full
+pub(crate) fn print_recent(history: &HistoryFile) {
+    let (hidden, messages) = history.recent_messages(RECENT_MESSAGES);
+    if hidden > 0 {
+        println!("{hidden} earlier messages are not shown");
+    }
+
+    let rendered: String = messages
+        .iter()
+        .map(|(role, content, metadata)| render_message(role, content, metadata.as_ref()))
+        .collect();
+    println!("{rendered}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_message() {
+        colored::control::set_override(false);
+        let separator = "-".repeat(67);
+        let metadata = MessageMetadata {
+            model: Some("gemma3:12b".to_string()),
+            output_tokens: Some(2),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            render_message("assistant", "Hi!", Some(&metadata)),
+            format!(
+                "\n\n{separator}\n                        --- AI Response ---\ngemma3:12b, 2 out tokens\n{separator}\nHi!"
+            )
+        );
+        assert_eq!(
+            render_message("user", "Hello", Some(&MessageMetadata::default())),
+            format!(
+                "\n\n{separator}\n                        --- User Input ---\n{separator}\nHello"
+            )
+        );
+    }
+}
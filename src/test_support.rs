@@ -22,19 +22,15 @@ use serde_json::Value;
 use std::{fs, io};
 use tempfile::TempDir;
 
+#[derive(Default)]
 pub struct TestMockClient {
     system_prompt: String,
-}
-
-impl Default for TestMockClient {
-    fn default() -> Self {
-        Self { system_prompt: String::new() }
-    }
+    enabled_tools: Option<Vec<String>>,
 }
 
 impl ChatClient for TestMockClient {
     fn generate_response(&self, _history_messages_json: Value, _user_prompt: &str, _context_content: Option<&str>) -> io::Result<ChatResponse> {
-        Ok(ChatResponse { content: String::new(), tool_calls: None })
+        Ok(ChatResponse { content: String::new(), tool_calls: None, usage: None })
     }
 
     fn generate_tool_response(&self, _tool_prompt: Value) -> io::Result<ChatResponse> { unreachable!() }
@@ -46,6 +42,10 @@ impl ChatClient for TestMockClient {
     fn update_system_prompt(&mut self, system_prompt: String) { self.system_prompt = system_prompt; }
 
     fn system_prompt(&self) -> String { self.system_prompt.clone() }
+
+    fn update_enabled_tools(&mut self, enabled_tools: Option<Vec<String>>) { self.enabled_tools = enabled_tools; }
+
+    fn enabled_tools(&self) -> Option<Vec<String>> { self.enabled_tools.clone() }
 }
 
 pub fn make_mock_client() -> Box<dyn ChatClient> {